@@ -1,12 +1,39 @@
 use std::fmt::{Display, Formatter};
 
-/// Errors that can occur when writing a sitemap.
+use crate::validation::SitemapDiagnostic;
+
+/// Errors that can occur when writing or reading a sitemap.
 #[derive(Debug)]
 pub enum SitemapError {
     /// Failed to open or create the file.
     FileOpen(String),
     /// Failed to write to the file.
     Write(String),
+    /// The shard file name pattern does not contain a `{}` placeholder.
+    InvalidPattern(String),
+    /// An entry uses an extension whose namespace was not declared on the
+    /// `<urlset>` element.
+    UndeclaredNamespace(String),
+    /// A limit of the sitemap protocol or one of its extensions was exceeded.
+    LimitExceeded(String),
+    /// A video entry violates the constraints of the video sitemap extension.
+    InvalidVideo(String),
+    /// Failed to read from the input.
+    Read(String),
+    /// The input is not a well-formed sitemap or sitemap index.
+    Parse {
+        /// The 1-based line where the error was detected.
+        line: usize,
+        /// The 1-based column (in bytes) where the error was detected.
+        column: usize,
+        /// A description of the problem.
+        message: String,
+    },
+    /// A string could not be parsed into the requested value.
+    InvalidValue(String),
+    /// A writer in strict mode refused an entry that violates the sitemap
+    /// protocol.
+    InvalidEntry(SitemapDiagnostic),
 }
 
 impl Display for SitemapError {
@@ -14,6 +41,22 @@ impl Display for SitemapError {
         match self {
             SitemapError::FileOpen(msg) => write!(f, "Failed to open file: {}", msg),
             SitemapError::Write(msg) => write!(f, "Failed to write: {}", msg),
+            SitemapError::InvalidPattern(pattern) => {
+                write!(f, "Invalid file name pattern: {}", pattern)
+            }
+            SitemapError::UndeclaredNamespace(prefix) => {
+                write!(f, "Namespace not declared: xmlns:{}", prefix)
+            }
+            SitemapError::LimitExceeded(msg) => write!(f, "Limit exceeded: {}", msg),
+            SitemapError::InvalidVideo(msg) => write!(f, "Invalid video: {}", msg),
+            SitemapError::Read(msg) => write!(f, "Failed to read: {}", msg),
+            SitemapError::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "Parse error at {}:{}: {}", line, column, message),
+            SitemapError::InvalidValue(msg) => write!(f, "Invalid value: {}", msg),
+            SitemapError::InvalidEntry(diagnostic) => write!(f, "Invalid {}", diagnostic),
         }
     }
 }
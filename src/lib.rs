@@ -9,6 +9,35 @@
 //! - Support for all sitemap properties (`loc`, `lastmod`, `changefreq`, `priority`)
 //! - Support for Sitemap Index (for large sites with 50,000+ URLs)
 //! - Write directly to file or build as String
+//! - Atomic file output through a temporary file renamed over the target
+//! - Stream entries one at a time to any `std::io::Write` sink
+//! - Automatic sharding into multiple sitemaps plus a generated index
+//! - Sitemap index built from the sitemap files of a directory
+//! - Gzip-compressed output (`gzip` feature)
+//! - Image sitemap extension (`image:image`)
+//! - Video sitemap extension (`video:video`) with spec validation
+//! - Google News sitemap extension (`news:news`)
+//! - hreflang alternates (`xhtml:link`) with reciprocal expansion
+//! - Streaming reader for sitemaps and sitemap indexes
+//! - Validation with per-entry diagnostics and a strict writer mode
+//! - Typed W3C Datetime setter and parser for `lastmod`, converting from
+//!   `SystemTime` and `chrono`/`time` types (`chrono` and `time` features)
+//! - Async writers for any `AsyncWrite`, fed from a `futures::Stream` (`tokio`
+//!   feature)
+//! - `Serialize`/`Deserialize` for the entry types (`serde` feature)
+//! - Fluent, validating `SitemapUrl` builder
+//! - Optional pretty-printed output, one element per line
+//! - Plain-text sitemaps with one URL per line
+//! - RSS 2.0 and Atom 1.0 "recently updated" feeds
+//! - robots.txt builder with `Sitemap:` lines, able to update an existing file
+//! - Static-site scanner that maps the HTML files of a directory to URLs
+//! - Diff between two sitemap versions, as a text or JSON report
+//! - Merging of several sources with conflict resolution for duplicate URLs
+//! - Configurable URL normalization with deduplication of equivalent URLs
+//! - Incremental regeneration that keeps `lastmod` for unchanged pages, using
+//!   a sidecar manifest of content hashes
+//! - `sitemap-writer` command-line tool for CSV / JSON Lines input (`cli`
+//!   feature)
 //!
 //! ## Quick Start
 //!
@@ -22,6 +51,7 @@
 //!         lastmod: Some("2024-01-01".to_string()),
 //!         changefreq: Some(SitemapChangeFreq::DAILY),
 //!         priority: Some(1.0),
+//!         ..SitemapUrl::default()
 //!     },
 //!     SitemapUrl::new("https://example.com/about/"),
 //! ]);
@@ -38,6 +68,33 @@
 //! assert!(result.is_ok());
 //! ```
 //!
+//! ## Streaming to Any Writer
+//!
+//! ```rust
+//! use sitemap_writer::{SitemapWriter, SitemapUrl};
+//!
+//! let mut writer = SitemapWriter::new(std::io::stdout()).unwrap();
+//! writer.push(&SitemapUrl::new("https://example.com/")).unwrap();
+//! writer.finish().unwrap();
+//! ```
+//!
+//! ## Sharded Sitemaps
+//!
+//! [`ShardedSitemapWriter`] rolls over to a new file every 50,000 URLs or 50 MB
+//! and writes the sitemap index when finished:
+//!
+//! ```rust,no_run
+//! use sitemap_writer::{ShardedSitemapWriter, SitemapUrl};
+//!
+//! let mut writer = ShardedSitemapWriter::new(
+//!     "public",
+//!     "sitemap-{}.xml",
+//!     "https://example.com/",
+//! ).unwrap();
+//! writer.push(&SitemapUrl::new("https://example.com/")).unwrap();
+//! writer.finish("sitemap_index.xml").unwrap();
+//! ```
+//!
 //! ## Sitemap Index
 //!
 //! For large sites with more than 50,000 URLs, use Sitemap Index to reference multiple sitemaps:
@@ -55,18 +112,78 @@
 //! ```
 
 mod error;
+mod sitemap_alternate;
+#[cfg(feature = "tokio")]
+mod sitemap_async;
+mod sitemap_datetime;
+mod sitemap_diff;
+mod sitemap_feed;
+mod sitemap_file;
+mod sitemap_format;
+mod sitemap_image;
+mod sitemap_incremental;
 mod sitemap_index;
+mod sitemap_merge;
+mod sitemap_news;
+mod sitemap_normalize;
+mod sitemap_reader;
+mod sitemap_robots;
+mod sitemap_scanner;
+mod sitemap_sharded;
+mod sitemap_text;
 mod sitemap_url;
+mod sitemap_url_builder;
+mod sitemap_video;
 mod sitemap_writer;
+mod validation;
 
 pub use error::SitemapError;
-pub use sitemap_index::{SitemapIndex, SitemapIndexWriter};
+pub use sitemap_alternate::SitemapAlternate;
+#[cfg(feature = "tokio")]
+pub use sitemap_async::{AsyncSitemapIndexWriter, AsyncSitemapWriter};
+pub use sitemap_datetime::{SitemapDateTime, SitemapDateTimePrecision, SitemapLastmod};
+pub use sitemap_diff::{SitemapDiff, SitemapField, SitemapUrlChange};
+pub use sitemap_feed::{SitemapFeedFormat, SitemapFeedWriter};
+pub use sitemap_file::SitemapFile;
+pub use sitemap_format::SitemapFormat;
+pub use sitemap_image::{MAX_IMAGES_PER_URL, SitemapImage};
+pub use sitemap_incremental::SitemapIncremental;
+pub use sitemap_index::{SitemapDirScan, SitemapIndex, SitemapIndexWriter};
+pub use sitemap_merge::{SitemapMergeStrategy, SitemapMerger};
+pub use sitemap_news::{MAX_URLS_PER_NEWS_SITEMAP, SitemapNews};
+pub use sitemap_normalize::{SitemapNormalizer, SitemapTrailingSlash};
+pub use sitemap_reader::{SitemapEntry, SitemapReader};
+pub use sitemap_robots::{SitemapRobots, SitemapRobotsGroup, SitemapRobotsRule};
+pub use sitemap_scanner::SitemapScanner;
+pub use sitemap_sharded::ShardedSitemapWriter;
+pub use sitemap_text::SitemapTextWriter;
 pub use sitemap_url::{SitemapChangeFreq, SitemapUrl};
-pub use sitemap_writer::SitemapWriter;
+pub use sitemap_url_builder::SitemapUrlBuilder;
+pub use sitemap_video::{
+    MAX_VIDEO_DESCRIPTION_LEN, MAX_VIDEO_DURATION, MAX_VIDEO_TAGS, SitemapVideo,
+    SitemapVideoPlatform, SitemapVideoPlatforms, SitemapVideoRelationship, SitemapVideoRestriction,
+    SitemapVideoUploader,
+};
+pub use sitemap_writer::{
+    MAX_SITEMAP_BYTES, MAX_URLS_PER_SITEMAP, SitemapNamespaces, SitemapWriter,
+};
+pub use validation::{MAX_LOC_LEN, SitemapDiagnostic, SitemapIssue};
 
 #[cfg(test)]
 mod tests {
-    use crate::{SitemapChangeFreq, SitemapIndex, SitemapIndexWriter, SitemapUrl, SitemapWriter};
+    use crate::{
+        ShardedSitemapWriter, SitemapAlternate, SitemapChangeFreq, SitemapDateTime,
+        SitemapDateTimePrecision, SitemapEntry, SitemapError, SitemapFormat, SitemapImage,
+        SitemapIndex, SitemapIndexWriter, SitemapIssue, SitemapLastmod, SitemapNamespaces,
+        SitemapNews, SitemapReader, SitemapUrl, SitemapVideo, SitemapVideoPlatform,
+        SitemapVideoPlatforms, SitemapVideoRelationship, SitemapVideoUploader, SitemapWriter,
+    };
+
+    fn test_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("sitemap-writer-{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.to_string_lossy().to_string()
+    }
 
     #[test]
     fn test_make() {
@@ -81,6 +198,7 @@ mod tests {
                     lastmod: Some("2021-01-01".to_string()),
                     changefreq: Some(SitemapChangeFreq::ALWAYS),
                     priority: Some(1.0),
+                    ..SitemapUrl::default()
                 },
                 SitemapUrl::new("https://example.com/contact/"),
                 SitemapUrl::new("https://example.com/contact/?test=1"),
@@ -97,6 +215,67 @@ mod tests {
         assert!(xml.contains("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
     }
 
+    #[test]
+    fn test_stream_matches_build() {
+        let urls = vec![
+            SitemapUrl {
+                loc: "https://example.com/".to_string(),
+                lastmod: Some("2024-01-01".to_string()),
+                changefreq: Some(SitemapChangeFreq::DAILY),
+                priority: Some(0.5),
+                ..SitemapUrl::default()
+            },
+            SitemapUrl::new("https://example.com/?a=1&b=2"),
+        ];
+        let mut writer = SitemapWriter::new(Vec::new()).unwrap();
+        for url in &urls {
+            writer.push(url).unwrap();
+        }
+        assert_eq!(writer.url_count(), 2);
+        let written = writer.bytes_written();
+        let bytes = writer.finish().unwrap();
+        assert!(written < bytes.len());
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            SitemapWriter::build(urls)
+        );
+    }
+
+    #[test]
+    fn test_stream_empty() {
+        let writer = SitemapWriter::new(Vec::new()).unwrap();
+        let xml = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains("</urlset>"));
+    }
+
+    #[test]
+    fn test_stream_limits() {
+        let mut writer = SitemapWriter::new(std::io::sink()).unwrap();
+        let url = SitemapUrl::new("https://example.com/");
+        for _ in 0..crate::MAX_URLS_PER_SITEMAP {
+            writer.push(&url).unwrap();
+        }
+        assert!(matches!(
+            writer.push(&url),
+            Err(SitemapError::LimitExceeded(_))
+        ));
+        assert_eq!(writer.url_count(), crate::MAX_URLS_PER_SITEMAP);
+
+        let mut writer = SitemapWriter::new(std::io::sink()).unwrap();
+        let url = SitemapUrl::new(&format!("https://example.com/{}", "a".repeat(1 << 20)));
+        let row_len = url.loc.len() + "<url><loc></loc></url>".len();
+        while writer.bytes_written() + row_len + "</urlset> ".len() <= crate::MAX_SITEMAP_BYTES {
+            writer.push(&url).unwrap();
+        }
+        let bytes_written = writer.bytes_written();
+        assert!(matches!(
+            writer.push(&url),
+            Err(SitemapError::LimitExceeded(_))
+        ));
+        assert_eq!(writer.bytes_written(), bytes_written);
+    }
+
     #[test]
     fn test_xml_escaping() {
         let xml = SitemapWriter::build(vec![SitemapUrl::new("https://example.com/?a=1&b=2")]);
@@ -119,6 +298,1945 @@ mod tests {
         assert!(url.priority.is_none());
     }
 
+    #[test]
+    fn test_sharded_rollover() {
+        let dir = test_dir("sharded");
+        let mut writer = ShardedSitemapWriter::new(&dir, "sitemap-{}.xml", "https://example.com")
+            .unwrap()
+            .with_limits(2, usize::MAX);
+        for i in 0..5 {
+            let mut url = SitemapUrl::new(&format!("https://example.com/{}", i));
+            url.lastmod = Some(format!("2024-01-0{}", i + 1));
+            writer.push(&url).unwrap();
+        }
+        let sitemaps = writer.finish("sitemap_index.xml").unwrap();
+
+        assert_eq!(sitemaps.len(), 3);
+        assert_eq!(sitemaps[0].loc, "https://example.com/sitemap-1.xml");
+        assert_eq!(sitemaps[0].lastmod.as_deref(), Some("2024-01-02"));
+        assert_eq!(sitemaps[2].lastmod.as_deref(), Some("2024-01-05"));
+
+        let shard = std::fs::read_to_string(format!("{}/sitemap-3.xml", dir)).unwrap();
+        assert!(shard.contains("<loc>https://example.com/4</loc>"));
+        assert!(shard.ends_with("</urlset> "));
+        let index = std::fs::read_to_string(format!("{}/sitemap_index.xml", dir)).unwrap();
+        assert!(index.contains("<loc>https://example.com/sitemap-2.xml</loc>"));
+    }
+
+    #[test]
+    fn test_sharded_byte_limit() {
+        let dir = test_dir("sharded-bytes");
+        let mut writer = ShardedSitemapWriter::new(&dir, "s{}.xml", "https://example.com/")
+            .unwrap()
+            .with_limits(50_000, 300);
+        for i in 0..10 {
+            writer
+                .push(&SitemapUrl::new(&format!("https://example.com/page/{}", i)))
+                .unwrap();
+        }
+        let sitemaps = writer.finish("index.xml").unwrap();
+        assert!(sitemaps.len() > 1);
+        for i in 1..=sitemaps.len() {
+            let len = std::fs::metadata(format!("{}/s{}.xml", dir, i))
+                .unwrap()
+                .len();
+            assert!(len <= 300);
+        }
+    }
+
+    #[test]
+    fn test_sharded_invalid_pattern() {
+        let res = ShardedSitemapWriter::new(&test_dir("pattern"), "sitemap.xml", "https://a/");
+        assert!(matches!(res, Err(SitemapError::InvalidPattern(_))));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_roundtrip() {
+        use std::io::Read;
+
+        let urls = vec![SitemapUrl::new("https://example.com/?a=1&b=2")];
+        let mut writer = SitemapWriter::new_gz(Vec::new()).unwrap();
+        writer.push(&urls[0]).unwrap();
+        let uncompressed = writer.bytes_written();
+        let compressed = writer.finish_gz().unwrap();
+
+        let mut xml = String::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut xml)
+            .unwrap();
+        assert_eq!(xml, SitemapWriter::build(urls));
+        assert!(uncompressed < xml.len());
+
+        let dir = test_dir("gzip");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = format!("{}/sitemap_index.xml.gz", dir);
+        SitemapIndexWriter::make_gz(&path, vec![SitemapIndex::new("https://example.com/1.xml")])
+            .unwrap();
+        let mut xml = String::new();
+        flate2::read::GzDecoder::new(std::fs::File::open(&path).unwrap())
+            .read_to_string(&mut xml)
+            .unwrap();
+        assert!(xml.ends_with("</sitemapindex>"));
+
+        let sitemaps = SitemapReader::open(&path).unwrap().read_sitemaps().unwrap();
+        assert_eq!(sitemaps[0].loc, "https://example.com/1.xml");
+    }
+
+    #[test]
+    fn test_sitemap_index_stream() {
+        let mut writer = SitemapIndexWriter::new(Vec::new()).unwrap();
+        writer
+            .push(&SitemapIndex::new("https://example.com/sitemap1.xml"))
+            .unwrap();
+        assert_eq!(writer.sitemap_count(), 1);
+        let xml = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert_eq!(
+            xml,
+            SitemapIndexWriter::build(vec![SitemapIndex::new("https://example.com/sitemap1.xml")])
+        );
+    }
+
+    #[test]
+    fn test_images() {
+        let xml = SitemapWriter::build(vec![SitemapUrl::new("https://example.com/")]);
+        assert!(!xml.contains("xmlns:image"));
+
+        let mut url = SitemapUrl::new("https://example.com/gallery/");
+        url.images
+            .push(SitemapImage::new("https://example.com/1.jpg"));
+        url.images.push(SitemapImage {
+            loc: "https://example.com/2.jpg".to_string(),
+            caption: Some("Tom & Jerry".to_string()),
+            ..SitemapImage::default()
+        });
+        let xml = SitemapWriter::build(vec![SitemapUrl::new("https://example.com/"), url]);
+        assert!(xml.contains(r#"xmlns:image="http://www.google.com/schemas/sitemap-image/1.1""#));
+        assert!(xml.contains(
+            "<image:image><image:loc>https://example.com/1.jpg</image:loc></image:image>"
+        ));
+        assert!(xml.contains("<image:caption>Tom &amp; Jerry</image:caption>"));
+    }
+
+    #[test]
+    fn test_images_checks() {
+        let mut url = SitemapUrl::new("https://example.com/");
+        url.images
+            .push(SitemapImage::new("https://example.com/1.jpg"));
+        let mut writer = SitemapWriter::new(Vec::new()).unwrap();
+        assert!(matches!(
+            writer.push(&url),
+            Err(SitemapError::UndeclaredNamespace(_))
+        ));
+
+        url.images = vec![SitemapImage::new("https://example.com/1.jpg"); 1001];
+        let namespaces = SitemapNamespaces::from_urls(std::slice::from_ref(&url));
+        assert!(namespaces.image);
+        let mut writer = SitemapWriter::with_namespaces(Vec::new(), namespaces).unwrap();
+        assert!(matches!(
+            writer.push(&url),
+            Err(SitemapError::LimitExceeded(_))
+        ));
+        url.images.truncate(1000);
+        assert!(writer.push(&url).is_ok());
+    }
+
+    fn test_video() -> SitemapVideo {
+        let mut video = SitemapVideo::new(
+            "https://example.com/thumbs/1.jpg",
+            "Grilling steaks",
+            "How to grill <perfect> steaks",
+        );
+        video.player_loc = Some("https://example.com/player?id=1&autoplay=1".to_string());
+        video
+    }
+
+    #[test]
+    fn test_videos() {
+        let mut video = test_video();
+        video.duration = Some(600);
+        video.family_friendly = Some(false);
+        video.platform = Some(SitemapVideoPlatforms {
+            relationship: SitemapVideoRelationship::ALLOW,
+            platforms: vec![SitemapVideoPlatform::WEB, SitemapVideoPlatform::TV],
+        });
+        video.uploader = Some(SitemapVideoUploader {
+            name: "GrillyMcGrillerson".to_string(),
+            info: Some("https://example.com/users/grillymcgrillerson".to_string()),
+        });
+        video.tags = vec!["steak".to_string(), "meat".to_string()];
+        let mut url = SitemapUrl::new("https://example.com/videos/1");
+        url.videos.push(video);
+
+        let xml = SitemapWriter::build(vec![url.clone()]);
+        assert!(xml.contains(r#"xmlns:video="http://www.google.com/schemas/sitemap-video/1.1""#));
+        assert!(!xml.contains("xmlns:image"));
+        assert!(xml.contains(
+            "<video:description>How to grill &lt;perfect&gt; steaks</video:description>"
+        ));
+        assert!(xml.contains(
+            "<video:player_loc>https://example.com/player?id=1&amp;autoplay=1</video:player_loc>"
+        ));
+        assert!(xml.contains("<video:duration>600</video:duration>"));
+        assert!(xml.contains("<video:family_friendly>no</video:family_friendly>"));
+        assert!(xml.contains(r#"<video:platform relationship="allow">web tv</video:platform>"#));
+        assert!(xml.contains(r#"<video:uploader info="https://example.com/users/grillymcgrillerson">GrillyMcGrillerson</video:uploader>"#));
+        assert!(
+            xml.contains("<video:tag>steak</video:tag><video:tag>meat</video:tag></video:video>")
+        );
+
+        let namespaces = SitemapNamespaces::from_urls(std::slice::from_ref(&url));
+        let mut writer = SitemapWriter::with_namespaces(Vec::new(), namespaces).unwrap();
+        assert!(writer.push(&url).is_ok());
+    }
+
+    #[test]
+    fn test_video_validation() {
+        assert!(test_video().validate().is_ok());
+
+        let mut video = test_video();
+        video.player_loc = None;
+        assert!(matches!(
+            video.validate(),
+            Err(SitemapError::InvalidVideo(_))
+        ));
+
+        for duration in [0, 28_801] {
+            let mut video = test_video();
+            video.duration = Some(duration);
+            assert!(video.validate().is_err());
+        }
+        let mut video = test_video();
+        video.duration = Some(28_800);
+        assert!(video.validate().is_ok());
+
+        let mut video = test_video();
+        video.tags = vec!["tag".to_string(); 33];
+        assert!(video.validate().is_err());
+
+        let mut video = test_video();
+        video.rating = Some(5.1);
+        assert!(video.validate().is_err());
+
+        let mut url = SitemapUrl::new("https://example.com/videos/1");
+        let mut video = test_video();
+        video.content_loc = Some(url.loc.clone());
+        url.videos.push(video);
+        let res = SitemapWriter::make(&format!("{}.xml", test_dir("video")), vec![url]);
+        assert!(matches!(res, Err(SitemapError::InvalidVideo(_))));
+    }
+
+    fn test_news_url(id: usize, date: &str) -> SitemapUrl {
+        let mut url = SitemapUrl::new(&format!("https://example.com/news/{}", id));
+        url.news = Some(SitemapNews::new(
+            "The Example Times",
+            "en",
+            date,
+            "Tom & Jerry merge",
+        ));
+        url
+    }
+
+    #[test]
+    fn test_news() {
+        let xml = SitemapWriter::build(vec![test_news_url(1, "2024-01-10")]);
+        assert!(xml.contains(r#"xmlns:news="http://www.google.com/schemas/sitemap-news/0.9""#));
+        assert!(xml.contains("<news:news><news:publication><news:name>The Example Times</news:name><news:language>en</news:language></news:publication><news:publication_date>2024-01-10</news:publication_date><news:title>Tom &amp; Jerry merge</news:title></news:news>"));
+
+        let namespaces = SitemapNamespaces {
+            news: true,
+            ..SitemapNamespaces::default()
+        };
+        let mut writer = SitemapWriter::with_namespaces(std::io::sink(), namespaces).unwrap();
+        for id in 0..1000 {
+            writer.push(&test_news_url(id, "2024-01-10")).unwrap();
+        }
+        assert!(matches!(
+            writer.push(&test_news_url(1000, "2024-01-10")),
+            Err(SitemapError::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn test_news_recent() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        // 2024-01-12T00:00:00Z
+        let now = UNIX_EPOCH + Duration::from_secs(1_705_017_600);
+        assert!(test_news_url(1, "2024-01-10").news.unwrap().is_recent(now));
+        assert!(
+            !test_news_url(1, "2024-01-10T00:59:59+01:00")
+                .news
+                .unwrap()
+                .is_recent(now)
+        );
+        assert!(
+            test_news_url(1, "2024-01-11T23:00:00-05:00")
+                .news
+                .unwrap()
+                .is_recent(now)
+        );
+        assert!(
+            !test_news_url(1, "2024-01-09T23:59:59Z")
+                .news
+                .unwrap()
+                .is_recent(now)
+        );
+        assert!(!test_news_url(1, "yesterday").news.unwrap().is_recent(now));
+
+        let namespaces = SitemapNamespaces {
+            news: true,
+            ..SitemapNamespaces::default()
+        };
+        let mut writer = SitemapWriter::with_namespaces(Vec::new(), namespaces)
+            .unwrap()
+            .skip_stale_news(now);
+        writer.push(&test_news_url(1, "2024-01-11")).unwrap();
+        writer.push(&test_news_url(2, "2024-01-01")).unwrap();
+        writer
+            .push(&SitemapUrl::new("https://example.com/"))
+            .unwrap();
+        assert_eq!(writer.url_count(), 2);
+        let xml = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert!(!xml.contains("https://example.com/news/2"));
+
+        let dir = test_dir("news-sharded");
+        let mut writer = ShardedSitemapWriter::new(&dir, "news-{}.xml", "https://example.com/")
+            .unwrap()
+            .with_namespaces(namespaces)
+            .skip_stale_news(now);
+        writer.push(&test_news_url(1, "2024-01-11")).unwrap();
+        writer.push(&test_news_url(2, "2024-01-01")).unwrap();
+        writer.finish("news_index.xml").unwrap();
+        let xml = std::fs::read_to_string(format!("{}/news-1.xml", dir)).unwrap();
+        assert!(xml.contains("https://example.com/news/1"));
+        assert!(!xml.contains("https://example.com/news/2"));
+    }
+
+    #[test]
+    fn test_alternates() {
+        let group = vec![
+            SitemapAlternate::new("en", "https://example.com/en/"),
+            SitemapAlternate::new("de", "https://example.com/de/"),
+            SitemapAlternate::new("ja", "https://example.com/ja/?a=1&b=2"),
+            SitemapAlternate::new("x-default", "https://example.com/"),
+        ];
+        let urls = SitemapAlternate::expand(&group);
+        assert_eq!(urls.len(), 4);
+        for url in &urls {
+            assert_eq!(url.alternates, group);
+        }
+
+        let xml = SitemapWriter::build(urls.clone());
+        assert!(xml.contains(r#"xmlns:xhtml="http://www.w3.org/1999/xhtml""#));
+        assert!(xml.contains(r#"<url><loc>https://example.com/de/</loc><xhtml:link rel="alternate" hreflang="en" href="https://example.com/en/"/>"#));
+        assert!(xml.contains(r#"<xhtml:link rel="alternate" hreflang="ja" href="https://example.com/ja/?a=1&amp;b=2"/>"#));
+        assert_eq!(xml.matches(r#"hreflang="x-default""#).count(), 4);
+
+        let mut writer = SitemapWriter::new(Vec::new()).unwrap();
+        assert!(matches!(
+            writer.push(&urls[0]),
+            Err(SitemapError::UndeclaredNamespace(_))
+        ));
+    }
+
+    #[test]
+    fn test_changefreq_from_str() {
+        assert_eq!(
+            "never".parse::<SitemapChangeFreq>().unwrap(),
+            SitemapChangeFreq::NEVER
+        );
+        assert_eq!(
+            " Hourly ".parse::<SitemapChangeFreq>().unwrap(),
+            SitemapChangeFreq::HOURLY
+        );
+        assert!(matches!(
+            "sometimes".parse::<SitemapChangeFreq>(),
+            Err(SitemapError::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn test_reader_roundtrip() {
+        let mut video = test_video();
+        video.duration = Some(42);
+        video.live = Some(true);
+        video.tags = vec!["a&b".to_string()];
+        let mut url = test_news_url(1, "2024-01-10");
+        url.lastmod = Some("2024-01-10".to_string());
+        url.changefreq = Some(SitemapChangeFreq::HOURLY);
+        url.priority = Some(0.3);
+        url.images.push(SitemapImage {
+            loc: "https://example.com/1.jpg".to_string(),
+            title: Some("<title>".to_string()),
+            ..SitemapImage::default()
+        });
+        url.videos.push(video);
+        url.alternates = vec![SitemapAlternate::new(
+            "de",
+            "https://example.com/de/?a=1&b=2",
+        )];
+        let urls = vec![url, SitemapUrl::new("https://example.com/?a=1&b=2")];
+
+        let xml = SitemapWriter::build(urls.clone());
+        let read = SitemapReader::new(xml.as_bytes()).read_urls().unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(SitemapWriter::build(read.clone()), xml);
+        assert_eq!(read[0].changefreq, Some(SitemapChangeFreq::HOURLY));
+        assert_eq!(read[0].images[0].title.as_deref(), Some("<title>"));
+        assert_eq!(read[0].videos[0].tags, vec!["a&b".to_string()]);
+        assert_eq!(read[0].alternates, urls[0].alternates);
+        assert_eq!(read[1].loc, "https://example.com/?a=1&b=2");
+
+        let xml = SitemapIndexWriter::build(vec![SitemapIndex {
+            loc: "https://example.com/sitemap1.xml".to_string(),
+            lastmod: Some("2024-01-01".to_string()),
+        }]);
+        let entries: Vec<SitemapEntry> = SitemapReader::new(xml.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert!(
+            matches!(&entries[..], [SitemapEntry::Sitemap(s)] if s.lastmod.as_deref() == Some("2024-01-01"))
+        );
+        assert!(SitemapReader::new(xml.as_bytes()).read_urls().is_err());
+    }
+
+    #[test]
+    fn test_reader_formatting() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- generated -->
+<!DOCTYPE urlset>
+<ns:urlset xmlns:ns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <ns:url>
+    <ns:loc> <![CDATA[https://example.com/?a=1&b=2]]> </ns:loc>
+    <ns:unknown><nested/></ns:unknown>
+    <ns:priority>1.0</ns:priority>
+  </ns:url>
+  <ns:url><ns:loc>https://example.com/&#x41;&#66;</ns:loc></ns:url>
+</ns:urlset>"#;
+        let urls = SitemapReader::new(xml.as_bytes()).read_urls().unwrap();
+        assert_eq!(urls[0].loc, "https://example.com/?a=1&b=2");
+        assert_eq!(urls[0].priority, Some(1.0));
+        assert_eq!(urls[1].loc, "https://example.com/AB");
+
+        let urls = SitemapReader::new("<urlset/>".as_bytes())
+            .read_urls()
+            .unwrap();
+        assert!(urls.is_empty());
+    }
+
+    #[test]
+    fn test_reader_errors() {
+        let parse_error = |xml: &str| match SitemapReader::new(xml.as_bytes()).read_urls() {
+            Err(SitemapError::Parse { line, column, .. }) => (line, column),
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+        assert_eq!(parse_error("<urlset>\n  <url><loc>a</lo></url>"), (2, 14));
+        assert_eq!(
+            parse_error("<urlset>\n<url>\n<changefreq>often</changefreq></url></urlset>"),
+            (3, 1)
+        );
+        assert_eq!(
+            parse_error("<urlset><url><loc>&nbsp;</loc></url></urlset>"),
+            (1, 25)
+        );
+        assert_eq!(parse_error("<html></html>"), (1, 1));
+        assert_eq!(parse_error("<urlset><url></url></urlset>"), (1, 9));
+        parse_error("<urlset><url><loc>a</loc></url>");
+        for priority in ["NaN", "inf", "-0.1", "1e9", "high"] {
+            let xml = format!(
+                "<urlset><url><loc>a</loc><priority>{}</priority></url></urlset>",
+                priority
+            );
+            assert_eq!(parse_error(&xml), (1, 26));
+        }
+    }
+
+    #[test]
+    fn test_datetime_roundtrip() {
+        for (value, precision) in [
+            ("2024", SitemapDateTimePrecision::YEAR),
+            ("2024-02", SitemapDateTimePrecision::MONTH),
+            ("2024-02-29", SitemapDateTimePrecision::DAY),
+            ("2024-02-29T10:30+01:00", SitemapDateTimePrecision::MINUTE),
+            (
+                "2024-02-29T10:30:15-05:30",
+                SitemapDateTimePrecision::SECOND,
+            ),
+            (
+                "2024-02-29T10:30:15.25+00:00",
+                SitemapDateTimePrecision::FRACTION,
+            ),
+        ] {
+            let date: SitemapDateTime = value.parse().unwrap();
+            assert_eq!(date.precision(), precision);
+            assert_eq!(date.to_string(), value);
+        }
+        let date: SitemapDateTime = "2024-01-15T10:30:00Z".parse().unwrap();
+        assert_eq!(date.to_string(), "2024-01-15T10:30:00+00:00");
+
+        for value in [
+            "2024-1-15",
+            "2024-02-30",
+            "2024T10:00Z",
+            "2024-01-15T10:00",
+            "",
+        ] {
+            assert!(matches!(
+                value.parse::<SitemapDateTime>(),
+                Err(SitemapError::InvalidValue(_))
+            ));
+        }
+        assert!(SitemapDateTime::date(2023, 2, 29).is_err());
+    }
+
+    #[test]
+    fn test_datetime_timestamp() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let a: SitemapDateTime = "2024-01-15T10:00:00+09:00".parse().unwrap();
+        let b: SitemapDateTime = "2024-01-15T01:00:00Z".parse().unwrap();
+        assert_eq!(a.timestamp(), b.timestamp());
+        assert_eq!(a.timestamp(), 1_705_280_400);
+
+        for timestamp in [0, 951_782_400, 1_709_164_800, 4_107_542_399, -86_400] {
+            let time = if timestamp >= 0 {
+                UNIX_EPOCH + Duration::from_secs(timestamp as u64)
+            } else {
+                UNIX_EPOCH - Duration::from_secs(-timestamp as u64)
+            };
+            assert_eq!(SitemapDateTime::from(time).timestamp(), timestamp);
+        }
+        let leap_day = UNIX_EPOCH + Duration::from_secs(1_709_164_800);
+        assert_eq!(
+            SitemapDateTime::from(leap_day).to_string(),
+            "2024-02-29T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_lastmod() {
+        assert!(matches!(
+            SitemapLastmod::from("2024-01-15"),
+            SitemapLastmod::DateTime(_)
+        ));
+        assert_eq!(
+            SitemapLastmod::from("<yesterday>"),
+            SitemapLastmod::Raw("<yesterday>".to_string())
+        );
+
+        let mut url = SitemapUrl::new("https://example.com/");
+        url.lastmod = Some("<yesterday>".to_string());
+        assert!(SitemapWriter::build(vec![url]).contains("<lastmod>&lt;yesterday&gt;</lastmod>"));
+
+        let mut url = SitemapUrl::new("https://example.com/");
+        url.set_lastmod(SitemapDateTime::date(2024, 1, 15).unwrap());
+        assert_eq!(
+            url.parsed_lastmod().unwrap().date_time(),
+            Some(&SitemapDateTime::date(2024, 1, 15).unwrap())
+        );
+        assert!(SitemapWriter::build(vec![url]).contains("<lastmod>2024-01-15</lastmod>"));
+
+        let mut sitemap = SitemapIndex::new("https://example.com/sitemap.xml");
+        sitemap.set_lastmod(std::time::UNIX_EPOCH);
+        assert_eq!(
+            sitemap.lastmod.as_deref(),
+            Some("1970-01-01T00:00:00+00:00")
+        );
+        sitemap.lastmod = Some("soon".to_string());
+        assert_eq!(
+            sitemap.parsed_lastmod(),
+            Some(SitemapLastmod::Raw("soon".to_string()))
+        );
+
+        // Shards pick the newest lastmod by instant, not by string.
+        let dir = test_dir("lastmod_shards");
+        let mut writer =
+            ShardedSitemapWriter::new(&dir, "sitemap-{}.xml", "https://example.com/").unwrap();
+        for lastmod in ["2024-01-15T12:00:00+09:00", "2024-01-15T04:00:00+00:00"] {
+            let mut url = SitemapUrl::new("https://example.com/");
+            url.lastmod = Some(lastmod.to_string());
+            writer.push(&url).unwrap();
+        }
+        let sitemaps = writer.finish("sitemap_index.xml").unwrap();
+        assert_eq!(
+            sitemaps[0].lastmod.as_deref(),
+            Some("2024-01-15T04:00:00+00:00")
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_lastmod_chrono() {
+        use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+
+        let offset = FixedOffset::east_opt(9 * 3_600).unwrap();
+        let date = offset.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+        assert_eq!(
+            SitemapLastmod::from(date).to_string(),
+            "2024-01-15T10:30:00+09:00"
+        );
+        let date = Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+        assert_eq!(SitemapDateTime::from(date).timestamp(), date.timestamp());
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        assert_eq!(SitemapLastmod::from(date).to_string(), "2024-01-15");
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_lastmod_time() {
+        use time::{Date, Month, OffsetDateTime, Time, UtcOffset};
+
+        let date = Date::from_calendar_date(2024, Month::January, 15).unwrap();
+        assert_eq!(SitemapLastmod::from(date).to_string(), "2024-01-15");
+        let date = OffsetDateTime::new_in_offset(
+            date,
+            Time::from_hms_milli(10, 30, 0, 500).unwrap(),
+            UtcOffset::from_hms(-5, -30, 0).unwrap(),
+        );
+        assert_eq!(
+            SitemapLastmod::from(date).to_string(),
+            "2024-01-15T10:30:00.5-05:30"
+        );
+        assert_eq!(
+            SitemapDateTime::from(date).timestamp(),
+            date.unix_timestamp()
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_async_writer() {
+        use crate::AsyncSitemapWriter;
+
+        let urls: Vec<SitemapUrl> = (0..100)
+            .map(|i| SitemapUrl::new(&format!("https://example.com/{}", i)))
+            .collect();
+        let mut writer = AsyncSitemapWriter::new(Vec::new()).await.unwrap();
+        writer
+            .push_all(futures::stream::iter(urls.clone()))
+            .await
+            .unwrap();
+        assert_eq!(writer.url_count(), 100);
+        let bytes = writer.finish().await.unwrap();
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            SitemapWriter::build(urls)
+        );
+
+        let mut url = SitemapUrl::new("https://example.com/");
+        url.images
+            .push(SitemapImage::new("https://example.com/a.png"));
+        let mut writer = AsyncSitemapWriter::new(Vec::new()).await.unwrap();
+        assert!(matches!(
+            writer.push(&url).await,
+            Err(SitemapError::UndeclaredNamespace(_))
+        ));
+        let mut writer = AsyncSitemapWriter::new(Vec::new())
+            .await
+            .unwrap()
+            .strict(true);
+        assert!(matches!(
+            writer.push(&SitemapUrl::new("")).await,
+            Err(SitemapError::InvalidEntry(_))
+        ));
+
+        let urls = vec![
+            SitemapUrl::new("https://Example.com/a"),
+            SitemapUrl::new("https://example.com/a?utm_source=x"),
+            SitemapUrl::new("https://example.com/b"),
+        ];
+        let mut writer = AsyncSitemapWriter::new(Vec::new())
+            .await
+            .unwrap()
+            .normalize(crate::SitemapNormalizer::new());
+        writer
+            .push_all(futures::stream::iter(urls.clone()))
+            .await
+            .unwrap();
+        assert_eq!(writer.url_count(), 2);
+        let mut sync = SitemapWriter::new(Vec::new())
+            .unwrap()
+            .normalize(crate::SitemapNormalizer::new());
+        for url in &urls {
+            sync.push(url).unwrap();
+        }
+        assert_eq!(writer.finish().await.unwrap(), sync.finish().unwrap());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_async_files() {
+        use crate::{AsyncSitemapIndexWriter, AsyncSitemapWriter};
+
+        let dir = test_dir("async_files");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = format!("{}/sitemap.xml", dir);
+        let mut url = SitemapUrl::new("https://example.com/");
+        url.images
+            .push(SitemapImage::new("https://example.com/a.png"));
+        AsyncSitemapWriter::make(&path, vec![url]).await.unwrap();
+        let urls = SitemapReader::open(&path).unwrap().read_urls().unwrap();
+        assert_eq!(urls[0].images.len(), 1);
+
+        let stream = futures::stream::iter(
+            (0..10).map(|i| SitemapUrl::new(&format!("https://example.com/{}", i))),
+        );
+        let count = AsyncSitemapWriter::make_stream(&path, SitemapNamespaces::default(), stream)
+            .await
+            .unwrap();
+        assert_eq!(count, 10);
+        let urls = SitemapReader::open(&path).unwrap().read_urls().unwrap();
+        assert_eq!(urls.len(), 10);
+
+        // A failed write leaves the previous file and no temporary file.
+        let mut url = SitemapUrl::new("https://example.com/");
+        url.images
+            .push(SitemapImage::new("https://example.com/a.png"));
+        let stream = futures::stream::iter(vec![url]);
+        assert!(matches!(
+            AsyncSitemapWriter::make_stream(&path, SitemapNamespaces::default(), stream).await,
+            Err(SitemapError::UndeclaredNamespace(_))
+        ));
+        let urls = SitemapReader::open(&path).unwrap().read_urls().unwrap();
+        assert_eq!(urls.len(), 10);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let path = format!("{}/sitemap_index.xml", dir);
+        AsyncSitemapIndexWriter::make(
+            &path,
+            vec![SitemapIndex::new("https://example.com/sitemap.xml")],
+        )
+        .await
+        .unwrap();
+        let sitemaps = SitemapReader::open(&path).unwrap().read_sitemaps().unwrap();
+        assert_eq!(sitemaps[0].loc, "https://example.com/sitemap.xml");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut url = SitemapUrl::new("https://example.com/");
+        url.lastmod = Some("2024-01-15".to_string());
+        url.changefreq = Some(SitemapChangeFreq::DAILY);
+        url.images
+            .push(SitemapImage::new("https://example.com/a.png"));
+        let json = serde_json::to_string(&url).unwrap();
+        assert_eq!(
+            json,
+            r#"{"loc":"https://example.com/","lastmod":"2024-01-15","changefreq":"daily","images":[{"loc":"https://example.com/a.png"}]}"#
+        );
+        let parsed: SitemapUrl = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        assert!(matches!(
+            parsed.parsed_lastmod(),
+            Some(SitemapLastmod::DateTime(_))
+        ));
+
+        let url: SitemapUrl = serde_json::from_str(
+            r#"{"loc":"https://example.com/","changefreq":"never","priority":0.5,"lastmod":"soon"}"#,
+        )
+        .unwrap();
+        assert_eq!(url.changefreq, Some(SitemapChangeFreq::NEVER));
+        assert_eq!(url.priority, Some(0.5));
+        assert_eq!(url.lastmod.as_deref(), Some("soon"));
+        assert!(url.news.is_none() && url.videos.is_empty());
+
+        assert!(serde_json::from_str::<SitemapUrl>(r#"{"changefreq":"daily"}"#).is_err());
+        assert!(serde_json::from_str::<SitemapUrl>(r#"{"loc":"/","changefreq":"Daily"}"#).is_err());
+
+        let sitemap: SitemapIndex =
+            serde_json::from_str(r#"{"loc":"https://example.com/sitemap.xml"}"#).unwrap();
+        assert!(sitemap.lastmod.is_none());
+        assert_eq!(
+            serde_json::to_string(&sitemap).unwrap(),
+            r#"{"loc":"https://example.com/sitemap.xml"}"#
+        );
+
+        let date: SitemapDateTime = serde_json::from_str(r#""2024-01-15T10:00:00Z""#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&date).unwrap(),
+            r#""2024-01-15T10:00:00+00:00""#
+        );
+        assert!(serde_json::from_str::<SitemapDateTime>(r#""2024-13""#).is_err());
+    }
+
+    #[test]
+    fn test_url_builder() {
+        let url = SitemapUrl::builder(String::from("https://example.com/"))
+            .lastmod(SitemapDateTime::date(2024, 1, 15).unwrap())
+            .changefreq(SitemapChangeFreq::WEEKLY)
+            .priority(0.5)
+            .image("https://example.com/a.png")
+            .image(SitemapImage {
+                caption: Some("B".to_string()),
+                ..SitemapImage::new("https://example.com/b.png")
+            })
+            .video(test_video())
+            .news(SitemapNews::new("Example", "en", "2024-01-15", "Title"))
+            .alternate("en", "https://example.com/")
+            .alternate("de", "https://example.com/de/")
+            .build()
+            .unwrap();
+        assert_eq!(url.loc, "https://example.com/");
+        assert_eq!(url.lastmod.as_deref(), Some("2024-01-15"));
+        assert_eq!(url.changefreq, Some(SitemapChangeFreq::WEEKLY));
+        assert_eq!(url.priority, Some(0.5));
+        assert_eq!(url.images.len(), 2);
+        assert_eq!(url.videos.len(), 1);
+        assert!(url.news.is_some());
+        assert_eq!(
+            url.alternates[1],
+            SitemapAlternate::new("de", "https://example.com/de/")
+        );
+
+        match SitemapUrl::builder("https://example.com/")
+            .priority(2.0)
+            .build()
+        {
+            Err(SitemapError::InvalidEntry(diagnostic)) => {
+                assert_eq!(
+                    diagnostic.issues,
+                    vec![SitemapIssue::PriorityOutOfRange(2.0)]
+                );
+            }
+            other => panic!("expected an invalid entry, got {:?}", other),
+        }
+        let mut video = test_video();
+        video.tags = vec!["tag".to_string(); 33];
+        assert!(matches!(
+            SitemapUrl::builder("https://example.com/")
+                .video(video)
+                .build(),
+            Err(SitemapError::InvalidVideo(_))
+        ));
+        let mut builder = SitemapUrl::builder("https://example.com/");
+        for i in 0..1001 {
+            builder = builder.image(format!("https://example.com/{}.png", i));
+        }
+        assert!(matches!(
+            builder.build(),
+            Err(SitemapError::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn test_pretty_format() {
+        let mut url = SitemapUrl::new("https://example.com/?a=1&b=2");
+        url.priority = Some(0.5);
+        url.images
+            .push(SitemapImage::new("https://example.com/a.png"));
+        url.alternates
+            .push(SitemapAlternate::new("de", "https://example.com/de/"));
+        let urls = vec![url, SitemapUrl::new("https://example.com/about/")];
+
+        let xml = SitemapWriter::build_with_format(urls.clone(), SitemapFormat::pretty());
+        assert_eq!(
+            xml,
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\"",
+                " xmlns:image=\"http://www.google.com/schemas/sitemap-image/1.1\"",
+                " xmlns:xhtml=\"http://www.w3.org/1999/xhtml\">\n",
+                "  <url>\n",
+                "    <loc>https://example.com/?a=1&amp;b=2</loc>\n",
+                "    <priority>0.5</priority>\n",
+                "    <xhtml:link rel=\"alternate\" hreflang=\"de\" href=\"https://example.com/de/\"/>\n",
+                "    <image:image>\n",
+                "      <image:loc>https://example.com/a.png</image:loc>\n",
+                "    </image:image>\n",
+                "  </url>\n",
+                "  <url>\n",
+                "    <loc>https://example.com/about/</loc>\n",
+                "  </url>\n",
+                "</urlset>\n",
+            )
+        );
+        assert_eq!(
+            SitemapWriter::build_with_format(urls.clone(), SitemapFormat::compact()),
+            SitemapWriter::build(urls.clone())
+        );
+
+        let namespaces = SitemapNamespaces::from_urls(&urls);
+        let mut writer =
+            SitemapWriter::with_format(Vec::new(), namespaces, SitemapFormat::pretty()).unwrap();
+        for url in &urls {
+            writer.push(url).unwrap();
+        }
+        assert_eq!(writer.bytes_written(), xml.len() - "</urlset>\n".len());
+        assert_eq!(String::from_utf8(writer.finish().unwrap()).unwrap(), xml);
+
+        let parsed = SitemapReader::new(xml.as_bytes()).read_urls().unwrap();
+        assert_eq!(parsed[0].loc, "https://example.com/?a=1&b=2");
+        assert_eq!(parsed[0].images.len(), 1);
+        assert_eq!(parsed[1].loc, "https://example.com/about/");
+
+        // Sharded sets lay out shards and index, and measure the size limit
+        // on the laid out XML.
+        let dir = test_dir("pretty-sharded");
+        let urls = vec![
+            SitemapUrl::new("https://example.com/a"),
+            SitemapUrl::new("https://example.com/b"),
+        ];
+        let max_bytes =
+            SitemapWriter::build_with_format(urls.clone(), SitemapFormat::pretty()).len() - 1;
+        let mut writer = ShardedSitemapWriter::new(&dir, "shard-{}.xml", "https://example.com/")
+            .unwrap()
+            .with_format(SitemapFormat::pretty())
+            .with_limits(usize::MAX, max_bytes);
+        for url in &urls {
+            writer.push(url).unwrap();
+        }
+        assert_eq!(writer.finish("index.xml").unwrap().len(), 2);
+        for (index, url) in urls.iter().enumerate() {
+            let shard =
+                std::fs::read_to_string(format!("{}/shard-{}.xml", dir, index + 1)).unwrap();
+            assert_eq!(
+                shard,
+                SitemapWriter::build_with_format(vec![url.clone()], SitemapFormat::pretty())
+            );
+        }
+        let index = std::fs::read_to_string(format!("{}/index.xml", dir)).unwrap();
+        assert!(index.contains("\n  <sitemap>\n    <loc>https://example.com/shard-1.xml</loc>\n"));
+
+        #[cfg(feature = "gzip")]
+        {
+            use std::io::Read;
+            let path = format!("{}/sitemap.xml.gz", dir);
+            SitemapWriter::make_gz_with_format(&path, urls.clone(), SitemapFormat::pretty())
+                .unwrap();
+            let mut xml = String::new();
+            flate2::read::GzDecoder::new(std::fs::File::open(&path).unwrap())
+                .read_to_string(&mut xml)
+                .unwrap();
+            assert_eq!(
+                xml,
+                SitemapWriter::build_with_format(urls.clone(), SitemapFormat::pretty())
+            );
+        }
+
+        let format = SitemapFormat {
+            newlines: true,
+            indent: 0,
+        };
+        let xml = SitemapIndexWriter::build_with_format(
+            vec![SitemapIndex {
+                loc: "https://example.com/sitemap.xml".to_string(),
+                lastmod: Some("2024-01-01".to_string()),
+            }],
+            format,
+        );
+        assert!(xml.ends_with(concat!(
+            "<sitemap>\n",
+            "<loc>https://example.com/sitemap.xml</loc>\n",
+            "<lastmod>2024-01-01</lastmod>\n",
+            "</sitemap>\n",
+            "</sitemapindex>\n",
+        )));
+    }
+
+    #[test]
+    fn test_text_writer() {
+        use crate::SitemapTextWriter;
+
+        let dir = test_dir("text");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = format!("{}/sitemap.txt", dir);
+        let mut url = SitemapUrl::new("https://example.com/?a=1&b=2");
+        url.priority = Some(0.5);
+        url.images
+            .push(SitemapImage::new("https://example.com/a.png"));
+        SitemapTextWriter::make(&path, vec![url, SitemapUrl::new("https://example.com/ü")])
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "https://example.com/?a=1&b=2\nhttps://example.com/ü\n"
+        );
+
+        for loc in ["", "https://example.com/\n", "https://example.com/\rx"] {
+            let mut writer = SitemapTextWriter::new(Vec::new());
+            assert!(matches!(
+                writer.push(&SitemapUrl::new(loc)),
+                Err(SitemapError::InvalidValue(_))
+            ));
+            assert_eq!(writer.bytes_written(), 0);
+        }
+
+        let mut writer = SitemapTextWriter::new(std::io::sink());
+        let url = SitemapUrl::new("https://example.com/");
+        for _ in 0..crate::MAX_URLS_PER_SITEMAP {
+            writer.push(&url).unwrap();
+        }
+        assert!(matches!(
+            writer.push(&url),
+            Err(SitemapError::LimitExceeded(_))
+        ));
+
+        let mut writer = SitemapTextWriter::new(std::io::sink());
+        let url = SitemapUrl::new(&format!("https://example.com/{}", "a".repeat(1 << 20)));
+        while writer.bytes_written() + url.loc.len() < crate::MAX_SITEMAP_BYTES {
+            writer.push(&url).unwrap();
+        }
+        assert!(matches!(
+            writer.push(&url),
+            Err(SitemapError::LimitExceeded(_))
+        ));
+    }
+
+    fn test_feed_urls() -> Vec<SitemapUrl> {
+        [
+            ("https://example.com/a?x=1&y=2", Some("2024-01-15")),
+            ("https://example.com/b", Some("2024-03-01T10:30:00+09:00")),
+            ("https://example.com/c", None),
+            ("https://example.com/d", Some("not a date")),
+            ("https://example.com/e", Some("2023-12-31T23:59:59-05:00")),
+        ]
+        .into_iter()
+        .map(|(loc, lastmod)| {
+            let mut url = SitemapUrl::new(loc);
+            url.lastmod = lastmod.map(String::from);
+            url
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_feed_rss() {
+        use crate::{SitemapFeedFormat, SitemapFeedWriter};
+
+        let feed = SitemapFeedWriter::new(
+            SitemapFeedFormat::RSS,
+            "Blog & News",
+            "https://example.com/",
+        )
+        .build(test_feed_urls());
+        assert_eq!(
+            feed,
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+                "<rss version=\"2.0\"><channel>",
+                "<title>Blog &amp; News</title>",
+                "<link>https://example.com/</link>",
+                "<description>Blog &amp; News</description>",
+                "<lastBuildDate>Fri, 01 Mar 2024 10:30:00 +0900</lastBuildDate>",
+                "<item><title>https://example.com/b</title><link>https://example.com/b</link>",
+                "<guid>https://example.com/b</guid><pubDate>Fri, 01 Mar 2024 10:30:00 +0900</pubDate></item>",
+                "<item><title>https://example.com/a?x=1&amp;y=2</title><link>https://example.com/a?x=1&amp;y=2</link>",
+                "<guid>https://example.com/a?x=1&amp;y=2</guid><pubDate>Mon, 15 Jan 2024 00:00:00 +0000</pubDate></item>",
+                "<item><title>https://example.com/e</title><link>https://example.com/e</link>",
+                "<guid>https://example.com/e</guid><pubDate>Sun, 31 Dec 2023 23:59:59 -0500</pubDate></item>",
+                "</channel></rss>",
+            )
+        );
+    }
+
+    #[test]
+    fn test_feed_atom() {
+        use crate::{SitemapFeedFormat, SitemapFeedWriter};
+
+        let writer =
+            SitemapFeedWriter::new(SitemapFeedFormat::ATOM, "Blog", "https://example.com/")
+                .with_description("Recently updated")
+                .with_limit(2);
+        let feed = writer.build(test_feed_urls());
+        assert!(feed.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\"><title>Blog</title><subtitle>Recently updated</subtitle>"));
+        assert!(feed.contains(
+            "<id>https://example.com/</id><updated>2024-03-01T10:30:00+09:00</updated><entry>"
+        ));
+        assert!(feed.contains("<link href=\"https://example.com/a?x=1&amp;y=2\"/>"));
+        assert!(feed.contains("<updated>2024-01-15T00:00:00+00:00</updated></entry></feed>"));
+        assert_eq!(feed.matches("<entry>").count(), 2);
+
+        let dir = test_dir("feed");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = format!("{}/atom.xml", dir);
+        writer.make(&path, test_feed_urls()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), feed);
+
+        let empty = writer.build(vec![SitemapUrl::new("https://example.com/")]);
+        assert!(empty.contains("<updated>") && !empty.contains("<entry>"));
+    }
+
+    #[test]
+    fn test_robots() {
+        use crate::{SitemapRobots, SitemapRobotsGroup};
+
+        let robots = SitemapRobots::new()
+            .group(
+                SitemapRobotsGroup::new("*")
+                    .disallow("/admin/")
+                    .allow("/admin/public/"),
+            )
+            .group(SitemapRobotsGroup::new("Googlebot").user_agent("Bingbot"))
+            .sitemap("https://example.com/sitemap_index.xml")
+            .sitemap("https://example.com/news.xml");
+        assert_eq!(
+            robots.build().unwrap(),
+            "User-agent: *\nDisallow: /admin/\nAllow: /admin/public/\n\n\
+             User-agent: Googlebot\nUser-agent: Bingbot\nDisallow:\n\n\
+             Sitemap: https://example.com/sitemap_index.xml\n\
+             Sitemap: https://example.com/news.xml\n"
+        );
+        assert_eq!(SitemapRobots::new().build().unwrap(), "");
+
+        let existing = "# Managed by hand\n\
+                        User-agent: bingbot\nuser-agent: GOOGLEBOT\nDisallow: /old/\nCrawl-delay: 2\n\n\
+                        User-agent: BadBot\nDisallow: /\n\n\
+                        Sitemap: https://example.com/news.xml\n\
+                        Sitemap: https://example.com/other.xml\n";
+        let updated = robots.update_str(existing).unwrap();
+        assert_eq!(
+            updated,
+            "# Managed by hand\n\
+             User-agent: Googlebot\nUser-agent: Bingbot\nDisallow:\nCrawl-delay: 2\n\n\
+             User-agent: BadBot\nDisallow: /\n\n\
+             Sitemap: https://example.com/news.xml\n\
+             Sitemap: https://example.com/other.xml\n\n\
+             User-agent: *\nDisallow: /admin/\nAllow: /admin/public/\n\n\
+             Sitemap: https://example.com/sitemap_index.xml\n"
+        );
+        // Updating again is a no-op.
+        assert_eq!(robots.update_str(&updated).unwrap(), updated);
+
+        // Comments inside a replaced group stay where they were.
+        let commented = "User-agent: *\n# Keep the admin private\nDisallow: /old/\n\
+                        # Legacy path\nDisallow: /legacy/\nCrawl-delay: 5\n";
+        let replace = SitemapRobots::new().group(SitemapRobotsGroup::new("*").disallow("/new/"));
+        assert_eq!(
+            replace.update_str(commented).unwrap(),
+            "User-agent: *\n# Keep the admin private\nDisallow: /new/\n\
+             # Legacy path\nCrawl-delay: 5\n"
+        );
+        assert_eq!(
+            replace
+                .update_str("User-agent: *\n# No rules yet\n")
+                .unwrap(),
+            "User-agent: *\nDisallow: /new/\n# No rules yet\n"
+        );
+
+        // CRLF line endings are kept.
+        let crlf = "User-agent: *\r\n# Private\r\nDisallow: /old/\r\n";
+        assert_eq!(
+            robots.update_str(crlf).unwrap(),
+            "User-agent: *\r\n# Private\r\nDisallow: /admin/\r\nAllow: /admin/public/\r\n\r\n\
+             User-agent: Googlebot\r\nUser-agent: Bingbot\r\nDisallow:\r\n\r\n\
+             Sitemap: https://example.com/sitemap_index.xml\r\n\
+             Sitemap: https://example.com/news.xml\r\n"
+        );
+
+        let dir = test_dir("robots");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = format!("{}/robots.txt", dir);
+        robots.update(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            robots.build().unwrap()
+        );
+        std::fs::write(&path, existing).unwrap();
+        robots.update(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), updated);
+        robots.make(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            robots.build().unwrap()
+        );
+
+        let injected = SitemapRobots::new().sitemap("https://example.com/\nDisallow: /");
+        assert!(matches!(
+            injected.build(),
+            Err(SitemapError::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn test_scanner() {
+        use crate::SitemapScanner;
+        use crate::sitemap_scanner::glob_match;
+
+        assert!(glob_match("**/*.html", "index.html"));
+        assert!(glob_match("**/*.html", "a/b/c.html"));
+        assert!(glob_match("drafts/**", "drafts/a/b.html"));
+        assert!(!glob_match("*.html", "blog/post.html"));
+        assert!(glob_match("blog/?.html", "blog/a.html"));
+        assert!(!glob_match("blog/?.html", "blog/ab.html"));
+        // Repeated wildcards must not backtrack exponentially.
+        let deep = format!("{}b", "a/".repeat(200));
+        assert!(!glob_match("**/**/**/**/**/**/*a", &deep));
+        assert!(glob_match("**/**/**/**/**/**/*b", &deep));
+
+        let dir = test_dir("scanner");
+        for file in [
+            "index.html",
+            "about.html",
+            "404.html",
+            "style.css",
+            "blog/index.html",
+            "blog/first post.html",
+            "blog/drafts/wip.html",
+            ".hidden/index.html",
+        ] {
+            let path = std::path::Path::new(&dir).join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "<html></html>").unwrap();
+        }
+
+        let scanner = SitemapScanner::new(&dir, "https://example.com")
+            .exclude("404.html")
+            .exclude("**/drafts/**");
+        let urls = scanner.scan().unwrap();
+        let locs: Vec<&str> = urls.iter().map(|url| url.loc.as_str()).collect();
+        assert_eq!(
+            locs,
+            vec![
+                "https://example.com/about.html",
+                "https://example.com/blog/first%20post.html",
+                "https://example.com/blog/",
+                "https://example.com/",
+            ]
+        );
+        assert!(urls.iter().all(|url| url.lastmod.is_some()));
+        assert!(SitemapWriter::validate(&urls).is_empty());
+
+        let urls = SitemapScanner::new(&dir, "https://example.com/")
+            .include("blog/**")
+            .scan()
+            .unwrap();
+        assert_eq!(urls.len(), 3);
+
+        let path = format!("{}/sitemap.xml", dir);
+        scanner.make(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            scanner.build().unwrap()
+        );
+
+        assert!(matches!(
+            SitemapScanner::new(&format!("{}/missing", dir), "https://example.com/").scan(),
+            Err(SitemapError::Read(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scanner_symlinks() {
+        use crate::SitemapScanner;
+        use std::os::unix::fs::symlink;
+
+        let dir = test_dir("scanner-symlinks");
+        std::fs::create_dir_all(format!("{}/blog", dir)).unwrap();
+        std::fs::write(format!("{}/index.html", dir), "").unwrap();
+        std::fs::write(format!("{}/blog/index.html", dir), "").unwrap();
+        std::fs::write(format!("{}/blog/post.html", dir), "").unwrap();
+        // Links back to an ancestor are not followed, other links are.
+        symlink(".", format!("{}/self", dir)).unwrap();
+        symlink("..", format!("{}/blog/up", dir)).unwrap();
+        symlink("blog", format!("{}/docs", dir)).unwrap();
+        // Dangling links are skipped.
+        symlink("missing.html", format!("{}/gone.html", dir)).unwrap();
+        symlink("missing", format!("{}/blog/gone", dir)).unwrap();
+
+        let urls = SitemapScanner::new(&dir, "https://example.com/")
+            .scan()
+            .unwrap();
+        let locs: Vec<&str> = urls.iter().map(|url| url.loc.as_str()).collect();
+        assert_eq!(
+            locs,
+            vec![
+                "https://example.com/blog/",
+                "https://example.com/blog/post.html",
+                "https://example.com/docs/",
+                "https://example.com/docs/post.html",
+                "https://example.com/",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        use crate::{SitemapDiff, SitemapField};
+
+        let mut page = SitemapUrl::new("https://example.com/page");
+        page.lastmod = Some("2024-01-01".to_string());
+        page.priority = Some(0.5);
+        let old = vec![
+            SitemapUrl::new("https://example.com/"),
+            page.clone(),
+            SitemapUrl::new("https://example.com/old?a=\"1\""),
+        ];
+        page.lastmod = Some("2024-02-01".to_string());
+        page.changefreq = Some(SitemapChangeFreq::WEEKLY);
+        let new = vec![
+            SitemapUrl::new("https://example.com/new"),
+            page,
+            SitemapUrl::new("https://example.com/"),
+            SitemapUrl::new("https://example.com/new"),
+        ];
+
+        let diff = SitemapDiff::new(&old, &new);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.removed[0].loc, "https://example.com/old?a=\"1\"");
+        assert_eq!(
+            diff.changed[0].fields,
+            vec![SitemapField::LASTMOD, SitemapField::CHANGEFREQ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "+ https://example.com/new\n\
+             - https://example.com/old?a=\"1\"\n\
+             ~ https://example.com/page\n    \
+             lastmod: 2024-01-01 -> 2024-02-01\n    \
+             changefreq: (none) -> weekly\n\
+             1 added, 1 removed, 1 changed\n"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&diff.to_json()).unwrap();
+        assert_eq!(json["removed"][0]["loc"], "https://example.com/old?a=\"1\"");
+        assert_eq!(json["removed"][0]["priority"], serde_json::Value::Null);
+        assert_eq!(json["changed"][0]["lastmod"]["old"], "2024-01-01");
+        assert_eq!(json["changed"][0]["changefreq"]["new"], "weekly");
+        assert!(json["changed"][0].get("priority").is_none());
+
+        assert!(SitemapDiff::new(&old, &old).is_empty());
+
+        let dir = test_dir("diff");
+        std::fs::create_dir_all(&dir).unwrap();
+        let old_path = format!("{}/old.xml", dir);
+        let new_path = format!("{}/new.xml", dir);
+        SitemapWriter::make(&old_path, old).unwrap();
+        SitemapWriter::make(&new_path, new).unwrap();
+        let from_files = SitemapDiff::from_files(&old_path, &new_path).unwrap();
+        assert_eq!(from_files.to_json(), diff.to_json());
+    }
+
+    #[test]
+    fn test_merge() {
+        use crate::{SitemapMergeStrategy, SitemapMerger};
+
+        let url = |loc: &str, lastmod: Option<&str>, priority: Option<f32>| SitemapUrl {
+            loc: format!("https://example.com/{}", loc),
+            lastmod: lastmod.map(Into::into),
+            priority,
+            ..SitemapUrl::default()
+        };
+        let team_a = vec![
+            url("a", Some("2024-01-01"), Some(0.4)),
+            url("b", None, Some(0.9)),
+            url("c", Some("2024-05-01"), None),
+        ];
+        let team_b = vec![
+            url("b", Some("2024-02-01"), None),
+            url("a", Some("2024-03-01"), Some(0.4)),
+            url("d", None, None),
+            url("c", Some("not a date"), Some(0.6)),
+        ];
+        let merge = |strategy| {
+            let mut merger = SitemapMerger::new(strategy);
+            merger.push_all(team_a.clone());
+            merger.push_all(team_b.clone());
+            merger
+        };
+
+        let merger = merge(SitemapMergeStrategy::NEWEST);
+        assert_eq!(merger.len(), 4);
+        let lastmods: Vec<Option<String>> = merger
+            .urls()
+            .iter()
+            .map(|url| url.lastmod.clone())
+            .collect();
+        assert_eq!(
+            lastmods,
+            vec![
+                Some("2024-03-01".to_string()),
+                Some("2024-02-01".to_string()),
+                Some("2024-05-01".to_string()),
+                None
+            ]
+        );
+        let locs: Vec<String> = merger.urls().iter().map(|url| url.loc.clone()).collect();
+        assert_eq!(
+            locs,
+            vec![
+                "https://example.com/a",
+                "https://example.com/b",
+                "https://example.com/c",
+                "https://example.com/d"
+            ]
+        );
+
+        let priorities: Vec<Option<f32>> = merge(SitemapMergeStrategy::PRIORITY)
+            .into_urls()
+            .iter()
+            .map(|url| url.priority)
+            .collect();
+        assert_eq!(priorities, vec![Some(0.4), Some(0.9), Some(0.6), None]);
+        // Ties keep the earlier entry.
+        let urls = merge(SitemapMergeStrategy::PRIORITY).into_urls();
+        assert_eq!(urls[0].lastmod.as_deref(), Some("2024-01-01"));
+
+        let urls = merge(SitemapMergeStrategy::FIRST).into_urls();
+        assert_eq!(urls[1].priority, Some(0.9));
+        assert!(urls[1].lastmod.is_none());
+
+        let dir = test_dir("merge");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path_a = format!("{}/a.xml", dir);
+        let path_b = format!("{}/b.xml", dir);
+        SitemapWriter::make(&path_a, team_a.clone()).unwrap();
+        SitemapWriter::make(&path_b, team_b.clone()).unwrap();
+        let mut merger = SitemapMerger::new(SitemapMergeStrategy::NEWEST);
+        merger.push_file(&path_a).unwrap();
+        merger.push_file(&path_b).unwrap();
+        assert_eq!(
+            merger.clone().build(),
+            merge(SitemapMergeStrategy::NEWEST).build()
+        );
+
+        let mut image = url("f", None, None);
+        image
+            .images
+            .push(SitemapImage::new("https://example.com/f.png"));
+        merger.push(url("e", None, None));
+        merger.push(image);
+        let writer = ShardedSitemapWriter::new(&dir, "merged-{}.xml", "https://example.com/")
+            .unwrap()
+            .with_limits(3, usize::MAX);
+        let sitemaps = merger.make_sharded(writer, "index.xml").unwrap();
+        assert_eq!(sitemaps.len(), 2);
+        let urls = SitemapReader::open(&format!("{}/merged-1.xml", dir))
+            .unwrap()
+            .read_urls()
+            .unwrap();
+        assert_eq!(urls.len(), 3);
+        let urls = SitemapReader::open(&format!("{}/merged-2.xml", dir))
+            .unwrap()
+            .read_urls()
+            .unwrap();
+        assert_eq!(urls[2].images.len(), 1);
+    }
+
+    #[test]
+    fn test_normalize() {
+        use crate::{SitemapMergeStrategy, SitemapNormalizer, SitemapTrailingSlash};
+
+        let normalizer = SitemapNormalizer::new();
+        for (loc, expected) in [
+            ("https://Example.COM/A/", "https://example.com/A/"),
+            ("HTTP://example.com:80", "http://example.com/"),
+            ("https://example.com:8443/a", "https://example.com:8443/a"),
+            ("http://example.com:443/a", "http://example.com:443/a"),
+            (
+                "https://user@Example.com:443/a",
+                "https://user@example.com/a",
+            ),
+            ("https://[::1]:443/a", "https://[::1]/a"),
+            (
+                "https://example.com/a?utm_source=x&UTM_Medium=y&gclid=1",
+                "https://example.com/a",
+            ),
+            (
+                "https://example.com/a?b=2&a=1&b=1&&fbclid=z#section",
+                "https://example.com/a?a=1&b=2&b=1",
+            ),
+            ("  https://example.com/a  ", "https://example.com/a"),
+            ("/relative/path", "/relative/path"),
+            ("mailto:a@example.com", "mailto:a@example.com"),
+        ] {
+            assert_eq!(normalizer.normalize(loc), expected, "{}", loc);
+        }
+
+        let add = SitemapNormalizer::new().trailing_slash(SitemapTrailingSlash::ADD);
+        assert_eq!(
+            add.normalize("https://example.com/a"),
+            "https://example.com/a/"
+        );
+        assert_eq!(
+            add.normalize("https://example.com/a.html"),
+            "https://example.com/a.html"
+        );
+        let remove = SitemapNormalizer::new().trailing_slash(SitemapTrailingSlash::REMOVE);
+        assert_eq!(
+            remove.normalize("https://example.com/a//"),
+            "https://example.com/a"
+        );
+        assert_eq!(
+            remove.normalize("https://example.com/"),
+            "https://example.com/"
+        );
+
+        let custom = SitemapNormalizer::new()
+            .lowercase(false)
+            .remove_default_port(false)
+            .strip_tracking(false)
+            .strip_param("ref_*")
+            .sort_query(false)
+            .remove_fragment(false);
+        assert_eq!(
+            custom.normalize("https://Example.com:443/a?utm_source=x&ref_id=1&b=2&a=1#top"),
+            "https://Example.com:443/a?utm_source=x&b=2&a=1#top"
+        );
+
+        let mut newer = SitemapUrl::new("https://example.com/a?utm_source=x");
+        newer.lastmod = Some("2024-02-01".to_string());
+        let urls = remove.dedup(
+            vec![
+                SitemapUrl::new("https://Example.com/a/"),
+                SitemapUrl::new("https://example.com/b"),
+                newer,
+            ],
+            SitemapMergeStrategy::NEWEST,
+        );
+        assert_eq!(urls.len(), 2);
+        assert_eq!(urls[0].loc, "https://example.com/a");
+        assert!(urls[0].lastmod.is_some());
+
+        let mut writer = SitemapWriter::new(Vec::new())
+            .unwrap()
+            .strict(true)
+            .normalize(remove.clone());
+        writer
+            .push(&SitemapUrl::new("https://Example.com/a/"))
+            .unwrap();
+        writer
+            .push(&SitemapUrl::new("https://example.com/a#x"))
+            .unwrap();
+        assert!(writer.push(&SitemapUrl::new("/relative")).is_err());
+        writer
+            .push(&SitemapUrl::new("https://example.com/b/"))
+            .unwrap();
+        assert_eq!(writer.url_count(), 2);
+        let xml = String::from_utf8(writer.finish().unwrap()).unwrap();
+        assert!(xml.contains("<loc>https://example.com/a</loc>"));
+        assert!(xml.contains("<loc>https://example.com/b</loc>"));
+
+        let dir = test_dir("normalize");
+        let mut writer = ShardedSitemapWriter::new(&dir, "sitemap-{}.xml", "https://example.com/")
+            .unwrap()
+            .with_limits(1, usize::MAX)
+            .normalize(SitemapNormalizer::new());
+        for loc in [
+            "https://example.com/a",
+            "https://example.com/b",
+            "https://EXAMPLE.com/a",
+        ] {
+            writer.push(&SitemapUrl::new(loc)).unwrap();
+        }
+        assert_eq!(writer.finish("index.xml").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_incremental() {
+        use crate::SitemapIncremental;
+
+        let dir = test_dir("incremental");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = format!("{}/sitemap.xml", dir);
+        let entries = |hashes: [&str; 2]| {
+            vec![
+                (
+                    SitemapUrl::new("https://example.com/"),
+                    hashes[0].to_string(),
+                ),
+                (
+                    SitemapUrl::new("https://example.com/a"),
+                    hashes[1].to_string(),
+                ),
+            ]
+        };
+        let lastmods = || -> Vec<String> {
+            SitemapReader::open(&path)
+                .unwrap()
+                .read_urls()
+                .unwrap()
+                .iter()
+                .map(|url| url.lastmod.clone().unwrap())
+                .collect()
+        };
+
+        // The first run has no manifest, so every page is new.
+        SitemapIncremental::open(&path)
+            .unwrap()
+            .with_now("2024-01-01")
+            .make(entries(["h1", "h2"]))
+            .unwrap();
+        assert_eq!(lastmods(), vec!["2024-01-01", "2024-01-01"]);
+        assert_eq!(
+            std::fs::read_to_string(format!("{}.manifest", path)).unwrap(),
+            "# sitemap-writer manifest v1\nh1\thttps://example.com/\nh2\thttps://example.com/a\n"
+        );
+
+        let mut incremental = SitemapIncremental::open(&path)
+            .unwrap()
+            .with_now("2024-02-01");
+        assert!(!incremental.is_changed("https://example.com/", "h1"));
+        assert!(incremental.is_changed("https://example.com/a", "h2-new"));
+        incremental.make(entries(["h1", "h2-new"])).unwrap();
+        assert_eq!(lastmods(), vec!["2024-01-01", "2024-02-01"]);
+
+        // A page that disappears is dropped from the manifest, and comes back
+        // as new.
+        let mut incremental = SitemapIncremental::open(&path)
+            .unwrap()
+            .with_now("2024-03-01");
+        incremental
+            .make(entries(["h1", "h2-new"])[..1].to_vec())
+            .unwrap();
+        let mut incremental = SitemapIncremental::open(&path)
+            .unwrap()
+            .with_now("2024-04-01");
+        incremental.make(entries(["h1", "h2-new"])).unwrap();
+        assert_eq!(lastmods(), vec!["2024-01-01", "2024-04-01"]);
+
+        // A sharded set is read back through its index.
+        let mut incremental = SitemapIncremental::with_manifest(
+            &format!("{}/index.xml", dir),
+            &format!("{}/hashes.txt", dir),
+        )
+        .unwrap()
+        .with_now("2024-05-01");
+        let urls = incremental.update_all(entries(["h1", "h2"]));
+        let mut writer = ShardedSitemapWriter::new(&dir, "shard-{}.xml", "https://example.com/")
+            .unwrap()
+            .with_limits(1, usize::MAX);
+        for url in &urls {
+            writer.push(url).unwrap();
+        }
+        writer.finish("index.xml").unwrap();
+        incremental.save_manifest().unwrap();
+
+        let mut incremental = SitemapIncremental::with_manifest(
+            &format!("{}/index.xml", dir),
+            &format!("{}/hashes.txt", dir),
+        )
+        .unwrap()
+        .with_now("2024-06-01");
+        let urls = incremental.update_all(entries(["h1", "h2-changed"]));
+        assert_eq!(urls[0].lastmod.as_deref(), Some("2024-05-01"));
+        assert_eq!(urls[1].lastmod.as_deref(), Some("2024-06-01"));
+
+        // Without a manifest, pages keep the lastmod of an existing sitemap
+        // and their hashes are recorded.
+        let mut dated = SitemapUrl::new("https://example.com/");
+        dated.lastmod = Some("2023-06-01".to_string());
+        SitemapWriter::make(&path, vec![dated, SitemapUrl::new("https://example.com/a")]).unwrap();
+        std::fs::remove_file(format!("{}.manifest", path)).unwrap();
+        SitemapIncremental::open(&path)
+            .unwrap()
+            .with_now("2024-07-01")
+            .make(entries(["h1", "h2"]))
+            .unwrap();
+        assert_eq!(lastmods(), vec!["2023-06-01", "2024-07-01"]);
+        assert_eq!(
+            std::fs::read_to_string(format!("{}.manifest", path)).unwrap(),
+            "# sitemap-writer manifest v1\nh1\thttps://example.com/\nh2\thttps://example.com/a\n"
+        );
+
+        std::fs::write(format!("{}/bad.manifest", dir), "no tab here\n").unwrap();
+        assert!(matches!(
+            SitemapIncremental::with_manifest(&path, &format!("{}/bad.manifest", dir)),
+            Err(SitemapError::Parse { line: 1, .. })
+        ));
+
+        // Hashes starting with `#` or holding tabs, line breaks and
+        // backslashes survive the manifest round trip.
+        let hashes = ["#h1", "h\t2\\n\r\n"];
+        let mut incremental = SitemapIncremental::open(&path).unwrap();
+        incremental.make(entries(hashes)).unwrap();
+        let incremental = SitemapIncremental::open(&path).unwrap();
+        assert!(!incremental.is_changed("https://example.com/", hashes[0]));
+        assert!(!incremental.is_changed("https://example.com/a", hashes[1]));
+        assert!(incremental.is_changed("https://example.com/a", "h"));
+
+        std::fs::write(format!("{}/bad.manifest", dir), "# header\nh\\x\tloc\n").unwrap();
+        assert!(matches!(
+            SitemapIncremental::with_manifest(&path, &format!("{}/bad.manifest", dir)),
+            Err(SitemapError::Parse { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_index_from_dir() {
+        let dir = test_dir("index-from-dir");
+        std::fs::create_dir_all(format!("{}/sub", dir)).unwrap();
+        let url = |loc: &str, lastmod: Option<&str>| {
+            let mut url = SitemapUrl::new(loc);
+            url.lastmod = lastmod.map(String::from);
+            url
+        };
+        SitemapWriter::make(
+            &format!("{}/a.xml", dir),
+            vec![
+                url("https://example.com/1", Some("2024-01-01")),
+                url("https://example.com/2", Some("2024-03-01T10:00:00+00:00")),
+                url("https://example.com/3", None),
+            ],
+        )
+        .unwrap();
+        SitemapWriter::make(
+            &format!("{}/b c.xml", dir),
+            vec![url("https://example.com/4", None)],
+        )
+        .unwrap();
+        #[cfg(feature = "gzip")]
+        SitemapWriter::make_gz(
+            &format!("{}/d.xml.gz", dir),
+            vec![url("https://example.com/5", Some("2024-05-01"))],
+        )
+        .unwrap();
+        // Without the gzip feature, compressed files are reported, not listed.
+        #[cfg(not(feature = "gzip"))]
+        std::fs::write(format!("{}/d.xml.gz", dir), "").unwrap();
+        std::fs::write(format!("{}/notes.txt", dir), "").unwrap();
+        std::fs::write(format!("{}/.hidden.xml", dir), "").unwrap();
+        std::fs::write(format!("{}/sub/e.xml", dir), "").unwrap();
+
+        let index = format!("{}/sitemap_index.xml", dir);
+        let scan = SitemapIndexWriter::make_from_dir(&index, &dir, "https://example.com/sitemaps")
+            .unwrap();
+        if cfg!(feature = "gzip") {
+            assert!(scan.errors.is_empty());
+        } else {
+            assert_eq!(scan.errors.len(), 1);
+            assert_eq!(scan.errors[0].0, "d.xml.gz");
+            assert!(
+                scan.errors[0]
+                    .1
+                    .to_string()
+                    .contains("requires the gzip feature")
+            );
+        }
+        let sitemaps = scan.sitemaps;
+        let locs: Vec<&str> = sitemaps.iter().map(|s| s.loc.as_str()).collect();
+        let mut expected = vec![
+            "https://example.com/sitemaps/a.xml",
+            "https://example.com/sitemaps/b%20c.xml",
+        ];
+        if cfg!(feature = "gzip") {
+            expected.push("https://example.com/sitemaps/d.xml.gz");
+        }
+        assert_eq!(locs, expected);
+        assert_eq!(
+            sitemaps[0].lastmod.as_deref(),
+            Some("2024-03-01T10:00:00+00:00")
+        );
+        // Without a <lastmod>, the file modification time is used.
+        assert!(sitemaps[1].parsed_lastmod().unwrap().timestamp().is_some());
+        #[cfg(feature = "gzip")]
+        assert_eq!(sitemaps[2].lastmod.as_deref(), Some("2024-05-01"));
+
+        // The index itself is skipped when the directory is scanned again.
+        let read = SitemapReader::open(&index)
+            .unwrap()
+            .read_sitemaps()
+            .unwrap();
+        assert_eq!(read.len(), sitemaps.len());
+        let rescanned =
+            SitemapIndexWriter::scan_dir(&dir, "https://example.com/sitemaps/").unwrap();
+        assert_eq!(rescanned.sitemaps.len(), sitemaps.len());
+
+        // Feeds written next to the shards are not sitemaps.
+        use crate::{SitemapFeedFormat, SitemapFeedWriter};
+        for (name, format) in [
+            ("feed.xml", SitemapFeedFormat::RSS),
+            ("atom.xml", SitemapFeedFormat::ATOM),
+        ] {
+            SitemapFeedWriter::new(format, "Example", "https://example.com/")
+                .make(
+                    &format!("{}/{}", dir, name),
+                    vec![url("https://example.com/1", Some("2024-01-01"))],
+                )
+                .unwrap();
+        }
+        let rescanned =
+            SitemapIndexWriter::scan_dir(&dir, "https://example.com/sitemaps/").unwrap();
+        let rescanned: Vec<&str> = rescanned.sitemaps.iter().map(|s| s.loc.as_str()).collect();
+        assert_eq!(rescanned, locs);
+
+        // A malformed file is reported without failing the whole directory.
+        std::fs::write(format!("{}/broken.xml", dir), "<urlset>").unwrap();
+        let scan = SitemapIndexWriter::scan_dir(&dir, "https://example.com/sitemaps/").unwrap();
+        assert_eq!(scan.sitemaps.len(), sitemaps.len());
+        assert_eq!(scan.errors[0].0, "broken.xml");
+        assert!(matches!(scan.errors[0].1, SitemapError::Parse { .. }));
+        assert!(
+            SitemapIndexWriter::scan_dir(&format!("{}/missing", dir), "https://example.com/")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_atomic_write() {
+        let dir = test_dir("atomic");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = format!("{}/sitemap.xml", dir);
+        let files = || -> Vec<String> {
+            let mut files: Vec<String> = std::fs::read_dir(&dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+                .collect();
+            files.sort();
+            files
+        };
+
+        SitemapWriter::make(&path, vec![SitemapUrl::new("https://example.com/")]).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(files(), vec!["sitemap.xml"]);
+
+        // An error while writing leaves the previous version in place.
+        let mut url = SitemapUrl::new("https://example.com/videos/1");
+        let mut video = test_video();
+        video.content_loc = Some(url.loc.clone());
+        url.videos.push(video);
+        let res = SitemapWriter::make(&path, vec![SitemapUrl::new("https://example.com/new"), url]);
+        assert!(matches!(res, Err(SitemapError::InvalidVideo(_))));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+        assert_eq!(files(), vec!["sitemap.xml"]);
+
+        // A shard that is never finished is not published.
+        let mut writer = ShardedSitemapWriter::new(&dir, "shard-{}.xml", "https://example.com/")
+            .unwrap()
+            .strict(true);
+        writer
+            .push(&SitemapUrl::new("https://example.com/a"))
+            .unwrap();
+        assert!(writer.push(&SitemapUrl::new("/relative")).is_err());
+        drop(writer);
+        assert_eq!(files(), vec!["sitemap.xml"]);
+
+        // The directory synced after the rename of a bare file name is the
+        // current one.
+        use crate::sitemap_file::parent_dir;
+        use std::path::Path;
+        assert_eq!(parent_dir(Path::new("sitemap.xml")), Path::new("."));
+        assert_eq!(parent_dir(Path::new(&path)), Path::new(&dir));
+
+        // Nothing is created when the directory does not exist.
+        let res = SitemapWriter::make(&format!("{}/missing/sitemap.xml", dir), vec![]);
+        assert!(matches!(res, Err(SitemapError::FileOpen(_))));
+        assert_eq!(files(), vec!["sitemap.xml"]);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut url = SitemapUrl::new("https://example.com/");
+        url.lastmod = Some("2024-01-15T12:00:00+09:00".to_string());
+        url.priority = Some(1.0);
+        assert!(url.validate().is_empty());
+
+        assert_eq!(SitemapUrl::new("").validate(), vec![SitemapIssue::EmptyLoc]);
+        for loc in [
+            "example.com/",
+            "/a",
+            "ftp://example.com/",
+            "https:///a",
+            "https://a b/",
+        ] {
+            assert_eq!(
+                SitemapUrl::new(loc).validate(),
+                vec![SitemapIssue::InvalidLoc(loc.to_string())]
+            );
+        }
+        let long = format!("https://example.com/{}", "a".repeat(2048));
+        assert_eq!(
+            SitemapUrl::new(&long).validate(),
+            vec![SitemapIssue::LocTooLong(2068)]
+        );
+
+        let mut url = SitemapUrl::new("https://example.com/");
+        url.priority = Some(7.0);
+        url.lastmod = Some("yesterday".to_string());
+        assert_eq!(
+            url.validate(),
+            vec![
+                SitemapIssue::InvalidLastmod("yesterday".to_string()),
+                SitemapIssue::PriorityOutOfRange(7.0),
+            ]
+        );
+        for lastmod in [
+            "2024",
+            "2024-02",
+            "2024-02-29",
+            "2024-02-29T10:00Z",
+            "2024-02-29T10:00:00.5-03:00",
+        ] {
+            let mut url = SitemapUrl::new("https://example.com/");
+            url.lastmod = Some(lastmod.to_string());
+            assert!(url.validate().is_empty(), "{}", lastmod);
+        }
+        for lastmod in [
+            "2023-02-29",
+            "2024-13",
+            "2024-01-01T10:00",
+            "2024-01-01T25:00Z",
+            "24-01-01",
+        ] {
+            let mut url = SitemapUrl::new("https://example.com/");
+            url.lastmod = Some(lastmod.to_string());
+            assert!(!url.validate().is_empty(), "{}", lastmod);
+        }
+
+        let diagnostics = SitemapIndexWriter::validate(&[
+            SitemapIndex::new("https://example.com/sitemap1.xml"),
+            SitemapIndex::new("sitemap2.xml"),
+        ]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].index, 1);
+        assert_eq!(diagnostics[0].loc, "sitemap2.xml");
+    }
+
+    #[test]
+    fn test_strict_mode() {
+        use crate::SitemapNormalizer;
+
+        let mut writer = SitemapWriter::new(Vec::new()).unwrap().strict(true);
+        writer
+            .push(&SitemapUrl::new("https://example.com/"))
+            .unwrap();
+        match writer.push(&SitemapUrl::new("")) {
+            Err(SitemapError::InvalidEntry(diagnostic)) => {
+                assert_eq!(diagnostic.index, 1);
+                assert_eq!(diagnostic.issues, vec![SitemapIssue::EmptyLoc]);
+            }
+            other => panic!("expected an invalid entry, got {:?}", other),
+        }
+        assert_eq!(writer.url_count(), 1);
+
+        let mut writer = SitemapWriter::new(Vec::new()).unwrap();
+        assert!(writer.push(&SitemapUrl::new("")).is_ok());
+
+        // Skipped duplicates still count towards the reported index.
+        let dir = test_dir("strict-index");
+        let urls = [
+            SitemapUrl::new("https://example.com/a"),
+            SitemapUrl::new("https://Example.com/a"),
+            SitemapUrl::new("/relative"),
+        ];
+        let index = |result: Result<(), SitemapError>| match result {
+            Err(SitemapError::InvalidEntry(diagnostic)) => diagnostic.index,
+            other => panic!("expected an invalid entry, got {:?}", other),
+        };
+        let mut writer = SitemapWriter::new(Vec::new())
+            .unwrap()
+            .strict(true)
+            .normalize(SitemapNormalizer::new());
+        writer.push(&urls[0]).unwrap();
+        writer.push(&urls[1]).unwrap();
+        assert_eq!(index(writer.push(&urls[2])), 2);
+        let mut writer = ShardedSitemapWriter::new(&dir, "s{}.xml", "https://example.com/")
+            .unwrap()
+            .strict(true)
+            .normalize(SitemapNormalizer::new());
+        writer.push(&urls[0]).unwrap();
+        writer.push(&urls[1]).unwrap();
+        assert_eq!(index(writer.push(&urls[2])), 2);
+
+        let mut writer = SitemapIndexWriter::new(Vec::new()).unwrap().strict(true);
+        assert!(matches!(
+            writer.push(&SitemapIndex::new("/sitemap.xml")),
+            Err(SitemapError::InvalidEntry(_))
+        ));
+    }
+
     #[test]
     fn test_sitemap_index_build() {
         let xml = SitemapIndexWriter::build(vec![
@@ -0,0 +1,323 @@
+//! The `sitemap-writer` command-line tool.
+//!
+//! Reads URL rows from CSV or JSON Lines and writes a sitemap, or a sharded
+//! set of sitemaps plus a sitemap index. Requires the `cli` feature:
+//!
+//! ```text
+//! cargo install sitemap-writer --features cli
+//! sitemap-writer urls.csv --output-dir public
+//! sitemap-writer urls.jsonl --output-dir public/sitemaps --base-url https://example.com/sitemaps/ --gzip
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, ValueEnum};
+use serde::Deserialize;
+use sitemap_writer::{
+    ShardedSitemapWriter, SitemapChangeFreq, SitemapError, SitemapFile, SitemapUrl, SitemapWriter,
+};
+
+/// Generates XML sitemaps from CSV or JSON Lines.
+///
+/// Each row describes one URL with the columns `loc` (required), `lastmod`,
+/// `changefreq` and `priority`. Other columns are ignored.
+#[derive(Debug, Parser)]
+#[command(name = "sitemap-writer", version)]
+struct Args {
+    /// The input file. Reads standard input if omitted or `-`.
+    input: Option<PathBuf>,
+
+    /// The input format. Guessed from the file extension if omitted,
+    /// defaulting to CSV.
+    #[arg(short, long, value_enum)]
+    format: Option<InputFormat>,
+
+    /// The directory the sitemap files are written to.
+    #[arg(short, long, default_value = ".")]
+    output_dir: PathBuf,
+
+    /// The public URL of the output directory. When set, URLs are split
+    /// across sharded sitemaps and a sitemap index is written. Required for
+    /// more than 50,000 URLs or 50 MB.
+    #[arg(short, long)]
+    base_url: Option<String>,
+
+    /// The sitemap file name, or the shard file name pattern containing
+    /// `{}` when `--base-url` is set. Defaults to `sitemap.xml` or
+    /// `sitemap-{}.xml`, with `.gz` appended when compressing.
+    #[arg(short, long)]
+    name: Option<String>,
+
+    /// The file name of the sitemap index.
+    #[arg(short, long, default_value = "sitemap_index.xml")]
+    index: String,
+
+    /// Compresses the sitemaps with gzip. The index is not compressed.
+    #[arg(short, long)]
+    gzip: bool,
+
+    /// Refuses rows that violate the sitemap protocol, such as relative URLs
+    /// or priorities outside 0.0-1.0.
+    #[arg(short, long)]
+    strict: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum InputFormat {
+    /// Comma-separated values with a header row.
+    Csv,
+    /// One JSON object per line.
+    Jsonl,
+}
+
+/// A row of the input.
+#[derive(Debug, Deserialize)]
+struct Row {
+    loc: String,
+    #[serde(default)]
+    lastmod: Option<String>,
+    #[serde(default)]
+    changefreq: Option<String>,
+    #[serde(default)]
+    priority: Option<f32>,
+}
+
+impl Row {
+    fn into_url(self) -> Result<SitemapUrl, String> {
+        let mut url = SitemapUrl::new(self.loc.trim());
+        url.lastmod = non_empty(self.lastmod);
+        url.changefreq = match non_empty(self.changefreq) {
+            Some(changefreq) => Some(
+                changefreq
+                    .parse::<SitemapChangeFreq>()
+                    .map_err(|e| e.to_string())?,
+            ),
+            None => None,
+        };
+        url.priority = self.priority;
+        Ok(url)
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+type Rows<'a> = Box<dyn Iterator<Item = Result<SitemapUrl, String>> + 'a>;
+
+/// Parses the input into URLs, one row at a time.
+///
+/// Errors are prefixed with the 1-based line of the offending row.
+fn read_rows<'a, R: Read + 'a>(reader: R, format: InputFormat) -> Rows<'a> {
+    match format {
+        InputFormat::Csv => {
+            let mut reader = csv::ReaderBuilder::new()
+                .trim(csv::Trim::All)
+                .from_reader(reader);
+            let headers = reader.headers().cloned();
+            Box::new(reader.into_records().map(move |record| {
+                let headers = headers.as_ref().map_err(|e| e.to_string())?;
+                let record = record.map_err(|e| e.to_string())?;
+                let line = record.position().map_or(0, |position| position.line());
+                record
+                    .deserialize::<Row>(Some(headers))
+                    .map_err(|e| e.to_string())
+                    .and_then(Row::into_url)
+                    .map_err(|e| format!("line {}: {}", line, e))
+            }))
+        }
+        InputFormat::Jsonl => Box::new(
+            BufReader::new(reader)
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+                .map(|(index, line)| {
+                    let line = line.map_err(|e| e.to_string())?;
+                    serde_json::from_str::<Row>(&line)
+                        .map_err(|e| e.to_string())
+                        .and_then(Row::into_url)
+                        .map_err(|e| format!("line {}: {}", index + 1, e))
+                }),
+        ),
+    }
+}
+
+/// Guesses the input format from the file extension.
+fn guess_format(input: Option<&Path>) -> InputFormat {
+    let extension = input
+        .and_then(|input| input.extension())
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("jsonl" | "ndjson") => InputFormat::Jsonl,
+        _ => InputFormat::Csv,
+    }
+}
+
+fn push_all<W: Write>(writer: &mut SitemapWriter<W>, rows: Rows) -> Result<usize, String> {
+    for url in rows {
+        // Rows have no images, so a limit can only be the size of the sitemap.
+        writer.push(&url?).map_err(|e| match e {
+            SitemapError::LimitExceeded(_) => format!(
+                "{}; pass --base-url to split the URLs across several sitemaps",
+                e
+            ),
+            e => e.to_string(),
+        })?;
+    }
+    Ok(writer.url_count())
+}
+
+fn write_sitemap(args: &Args, rows: Rows) -> Result<(), String> {
+    let name = match &args.name {
+        Some(name) => name.clone(),
+        None if args.gzip => "sitemap.xml.gz".to_string(),
+        None => "sitemap.xml".to_string(),
+    };
+    fs::create_dir_all(&args.output_dir).map_err(|e| e.to_string())?;
+    let path = args.output_dir.join(name);
+    let file = if args.gzip {
+        SitemapFile::create_gz(&path)
+    } else {
+        SitemapFile::create(&path)
+    };
+    let file = file.map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let mut writer = SitemapWriter::new(file)
+        .map_err(|e| e.to_string())?
+        .strict(args.strict);
+    let count = push_all(&mut writer, rows)?;
+    writer
+        .finish()
+        .map_err(|e| e.to_string())?
+        .close()
+        .map_err(|e| e.to_string())?;
+    eprintln!("Wrote {} URLs to {}", count, path.display());
+    Ok(())
+}
+
+fn write_sharded(args: &Args, base_url: &str, rows: Rows) -> Result<(), String> {
+    let pattern = match &args.name {
+        Some(name) => name.clone(),
+        None if args.gzip => "sitemap-{}.xml.gz".to_string(),
+        None => "sitemap-{}.xml".to_string(),
+    };
+    let mut writer =
+        ShardedSitemapWriter::new(&args.output_dir.to_string_lossy(), &pattern, base_url)
+            .map_err(|e| e.to_string())?
+            .strict(args.strict)
+            .with_gzip(args.gzip);
+    let mut count = 0;
+    for url in rows {
+        writer.push(&url?).map_err(|e| e.to_string())?;
+        count += 1;
+    }
+    let sitemaps = writer.finish(&args.index).map_err(|e| e.to_string())?;
+    eprintln!(
+        "Wrote {} URLs to {} sitemaps and {}",
+        count,
+        sitemaps.len(),
+        args.output_dir.join(&args.index).display()
+    );
+    Ok(())
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let input = args
+        .input
+        .as_deref()
+        .filter(|input| *input != Path::new("-"));
+    let format = args.format.unwrap_or_else(|| guess_format(input));
+    let reader: Box<dyn Read> = match input {
+        Some(input) => {
+            Box::new(File::open(input).map_err(|e| format!("{}: {}", input.display(), e))?)
+        }
+        None => Box::new(io::stdin().lock()),
+    };
+    let rows = read_rows(reader, format);
+    match &args.base_url {
+        Some(base_url) => write_sharded(args, base_url, rows),
+        None => write_sitemap(args, rows),
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("sitemap-writer: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_csv() {
+        let input = "loc,lastmod,changefreq,priority,title\n\
+                     https://example.com/,2024-01-15,daily,1.0,Home\n\
+                     https://example.com/about/,,,,About\n";
+        let urls: Vec<SitemapUrl> = read_rows(input.as_bytes(), InputFormat::Csv)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(urls.len(), 2);
+        assert_eq!(urls[0].lastmod.as_deref(), Some("2024-01-15"));
+        assert_eq!(urls[0].changefreq, Some(SitemapChangeFreq::DAILY));
+        assert_eq!(urls[0].priority, Some(1.0));
+        assert!(urls[1].lastmod.is_none() && urls[1].changefreq.is_none());
+
+        let input = "loc,changefreq\nhttps://example.com/,sometimes\n";
+        let errors: Vec<_> = read_rows(input.as_bytes(), InputFormat::Csv).collect();
+        assert!(errors[0].as_ref().unwrap_err().starts_with("line 2: "));
+    }
+
+    #[test]
+    fn test_read_jsonl() {
+        let input = "{\"loc\": \"https://example.com/\", \"priority\": 0.5}\n\n\
+                     {\"loc\": \"https://example.com/a\", \"changefreq\": \"weekly\"}\n\
+                     {\"lastmod\": \"2024-01-15\"}\n";
+        let rows: Vec<_> = read_rows(input.as_bytes(), InputFormat::Jsonl).collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].as_ref().unwrap().priority, Some(0.5));
+        assert_eq!(
+            rows[1].as_ref().unwrap().changefreq,
+            Some(SitemapChangeFreq::WEEKLY)
+        );
+        assert!(rows[2].as_ref().unwrap_err().starts_with("line 4: "));
+    }
+
+    #[test]
+    fn test_write_sitemap_limit() {
+        let dir = std::env::temp_dir().join("sitemap-writer-cli-limit");
+        let _ = fs::remove_dir_all(&dir);
+        let args = Args::parse_from(["sitemap-writer", "--output-dir", &dir.to_string_lossy()]);
+        let rows: Rows = Box::new(
+            (0..=sitemap_writer::MAX_URLS_PER_SITEMAP)
+                .map(|i| Ok(SitemapUrl::new(&format!("https://example.com/{}", i)))),
+        );
+        let error = write_sitemap(&args, rows).unwrap_err();
+        assert!(error.contains("at most 50000 URLs"), "{}", error);
+        assert!(error.contains("--base-url"), "{}", error);
+        // The partial sitemap is discarded.
+        assert!(!dir.join("sitemap.xml").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_guess_format() {
+        assert_eq!(guess_format(None), InputFormat::Csv);
+        assert_eq!(
+            guess_format(Some(Path::new("urls.JSONL"))),
+            InputFormat::Jsonl
+        );
+        assert_eq!(guess_format(Some(Path::new("urls.csv"))), InputFormat::Csv);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::sitemap_url::SitemapUrl;
+
+/// Represents a localized alternate of a page, written as
+/// `<xhtml:link rel="alternate" hreflang="..." href="..."/>`.
+///
+/// Use `x-default` as `hreflang` for the fallback page shown to users whose
+/// language is not listed.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapAlternate, SitemapUrl};
+///
+/// let mut url = SitemapUrl::new("https://example.com/en/");
+/// url.alternates.push(SitemapAlternate::new("en", "https://example.com/en/"));
+/// url.alternates.push(SitemapAlternate::new("de", "https://example.com/de/"));
+/// url.alternates.push(SitemapAlternate::new("x-default", "https://example.com/"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SitemapAlternate {
+    /// The language (and optional region) code, e.g. `en`, `de-CH` or
+    /// `x-default`.
+    pub hreflang: String,
+
+    /// The URL of the localized page.
+    pub href: String,
+}
+
+impl SitemapAlternate {
+    /// Creates a new `SitemapAlternate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `hreflang` - The language code of the alternate page.
+    /// * `href` - The URL of the alternate page.
+    pub fn new(hreflang: &str, href: &str) -> SitemapAlternate {
+        SitemapAlternate {
+            hreflang: hreflang.to_string(),
+            href: href.to_string(),
+        }
+    }
+
+    /// Expands a group of localized versions of the same page into one
+    /// [`SitemapUrl`] per distinct URL, each listing every member of the group
+    /// (including itself) as an alternate.
+    ///
+    /// Search engines ignore hreflang annotations that are not reciprocal;
+    /// this helper guarantees that every page links back to all others.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - All localized versions of a page, optionally including
+    ///   an `x-default` entry.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::SitemapAlternate;
+    ///
+    /// let urls = SitemapAlternate::expand(&[
+    ///     SitemapAlternate::new("en", "https://example.com/en/"),
+    ///     SitemapAlternate::new("de", "https://example.com/de/"),
+    ///     SitemapAlternate::new("x-default", "https://example.com/en/"),
+    /// ]);
+    /// assert_eq!(urls.len(), 2);
+    /// assert_eq!(urls[1].loc, "https://example.com/de/");
+    /// assert_eq!(urls[1].alternates.len(), 3);
+    /// ```
+    pub fn expand(group: &[SitemapAlternate]) -> Vec<SitemapUrl> {
+        let mut urls: Vec<SitemapUrl> = vec![];
+        for alternate in group {
+            if urls.iter().any(|url| url.loc == alternate.href) {
+                continue;
+            }
+            let mut url = SitemapUrl::new(&alternate.href);
+            url.alternates = group.to_vec();
+            urls.push(url);
+        }
+        urls
+    }
+}
+
+/// Renders a single `<xhtml:link>` element.
+pub(crate) fn render_alternate(alternate: &SitemapAlternate) -> String {
+    format!(
+        r#"<xhtml:link rel="alternate" hreflang="{}" href="{}"/>"#,
+        html_escape::encode_double_quoted_attribute(alternate.hreflang.as_str()),
+        html_escape::encode_double_quoted_attribute(alternate.href.as_str())
+    )
+}
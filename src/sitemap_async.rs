@@ -0,0 +1,524 @@
+use std::path::Path;
+use std::time::SystemTime;
+
+use futures::{Stream, StreamExt};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
+
+use crate::error::SitemapError;
+use crate::sitemap_file::{TempFile, parent_dir};
+use crate::sitemap_format::{SitemapFormat, XML_HEADER};
+use crate::sitemap_index::{
+    SITEMAPINDEX_CLOSE, SITEMAPINDEX_OPEN, SitemapIndex, check_valid_sitemap, render_sitemap,
+};
+use crate::sitemap_normalize::{SitemapDedup, SitemapNormalizer};
+use crate::sitemap_url::SitemapUrl;
+use crate::sitemap_writer::{
+    SitemapNamespaces, URLSET_CLOSE, check_limits, prepare_url, urlset_open,
+};
+
+/// An asynchronous writer for generating XML sitemaps.
+///
+/// The async counterpart of [`crate::SitemapWriter`]: entries are written to
+/// any [`tokio::io::AsyncWrite`] sink one at a time, and
+/// [`AsyncSitemapWriter::push_all`] consumes a [`futures::Stream`] of
+/// [`SitemapUrl`], so rows can be streamed straight from a database cursor
+/// without blocking the runtime.
+///
+/// Requires the `tokio` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use futures::stream;
+/// use sitemap_writer::{AsyncSitemapWriter, SitemapUrl};
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let urls = stream::iter((1..=3).map(|i| SitemapUrl::new(&format!("https://example.com/{}", i))));
+///
+/// let mut writer = AsyncSitemapWriter::new(Vec::new()).await.unwrap();
+/// writer.push_all(urls).await.unwrap();
+/// let xml = String::from_utf8(writer.finish().await.unwrap()).unwrap();
+/// assert!(xml.contains("<loc>https://example.com/3</loc>"));
+/// # });
+/// ```
+pub struct AsyncSitemapWriter<W: AsyncWrite + Unpin = File> {
+    writer: W,
+    namespaces: SitemapNamespaces,
+    format: SitemapFormat,
+    news_cutoff: Option<SystemTime>,
+    dedup: Option<SitemapDedup>,
+    strict: bool,
+    entry_count: usize,
+    url_count: usize,
+    bytes_written: usize,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncSitemapWriter<W> {
+    /// Starts a new sitemap on the given writer.
+    ///
+    /// The XML declaration and the opening `<urlset>` tag are written
+    /// immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Any [`tokio::io::AsyncWrite`] sink, e.g. a
+    ///   `tokio::fs::File`, a `TcpStream` or `Vec<u8>`.
+    pub async fn new(writer: W) -> Result<AsyncSitemapWriter<W>, SitemapError> {
+        AsyncSitemapWriter::with_namespaces(writer, SitemapNamespaces::default()).await
+    }
+
+    /// Starts a new sitemap on the given writer, declaring the given
+    /// extension namespaces on the `<urlset>` element.
+    ///
+    /// See [`crate::SitemapWriter::with_namespaces`].
+    pub async fn with_namespaces(
+        writer: W,
+        namespaces: SitemapNamespaces,
+    ) -> Result<AsyncSitemapWriter<W>, SitemapError> {
+        AsyncSitemapWriter::with_format(writer, namespaces, SitemapFormat::default()).await
+    }
+
+    /// Starts a new sitemap on the given writer, declaring the given
+    /// extension namespaces and laying out the XML with the given format.
+    ///
+    /// See [`crate::SitemapWriter::with_format`].
+    pub async fn with_format(
+        writer: W,
+        namespaces: SitemapNamespaces,
+        format: SitemapFormat,
+    ) -> Result<AsyncSitemapWriter<W>, SitemapError> {
+        let mut sitemap = AsyncSitemapWriter {
+            writer,
+            namespaces,
+            format,
+            news_cutoff: None,
+            dedup: None,
+            strict: false,
+            entry_count: 0,
+            url_count: 0,
+            bytes_written: 0,
+        };
+        sitemap.write_text(&format.line(XML_HEADER, 0)).await?;
+        sitemap
+            .write_text(&format.line(&urlset_open(&namespaces), 0))
+            .await?;
+        Ok(sitemap)
+    }
+
+    /// Writes a single `<url>` entry.
+    ///
+    /// Performs the same checks as [`crate::SitemapWriter::push`].
+    pub async fn push(&mut self, url: &SitemapUrl) -> Result<(), SitemapError> {
+        let index = self.entry_count;
+        self.entry_count += 1;
+        let normalized = match &self.dedup {
+            Some(dedup) => match dedup.prepare(url) {
+                Some(normalized) => Some(normalized),
+                None => return Ok(()),
+            },
+            None => None,
+        };
+        let url = normalized.as_ref().unwrap_or(url);
+        let row = prepare_url(
+            url,
+            &self.namespaces,
+            self.news_cutoff,
+            self.strict,
+            index,
+            self.url_count,
+        )?;
+        if let Some(row) = row {
+            let row = self.format.element(&row, 1).into_owned();
+            let close = self.format.line(URLSET_CLOSE, 0);
+            check_limits(self.url_count, self.bytes_written + row.len() + close.len())?;
+            self.write_text(&row).await?;
+            self.url_count += 1;
+            if let Some(dedup) = &mut self.dedup {
+                dedup.mark_written(&url.loc);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes every URL of a stream, stopping at the first error.
+    ///
+    /// # Arguments
+    ///
+    /// * `urls` - A [`futures::Stream`] of [`SitemapUrl`].
+    pub async fn push_all<S>(&mut self, urls: S) -> Result<(), SitemapError>
+    where
+        S: Stream<Item = SitemapUrl>,
+    {
+        let mut urls = std::pin::pin!(urls);
+        while let Some(url) = urls.next().await {
+            self.push(&url).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes every URL of a fallible stream, such as a database cursor,
+    /// stopping at the first error of either the stream or the writer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use futures::stream;
+    /// use sitemap_writer::{AsyncSitemapWriter, SitemapError, SitemapUrl};
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let rows = stream::iter(vec![
+    ///     Ok(SitemapUrl::new("https://example.com/")),
+    ///     Err(SitemapError::Read("connection lost".to_string())),
+    /// ]);
+    ///
+    /// let mut writer = AsyncSitemapWriter::new(Vec::new()).await.unwrap();
+    /// assert!(writer.try_push_all(rows).await.is_err());
+    /// assert_eq!(writer.url_count(), 1);
+    /// # });
+    /// ```
+    pub async fn try_push_all<S, E>(&mut self, urls: S) -> Result<(), E>
+    where
+        S: Stream<Item = Result<SitemapUrl, E>>,
+        E: From<SitemapError>,
+    {
+        let mut urls = std::pin::pin!(urls);
+        while let Some(url) = urls.next().await {
+            self.push(&url?).await?;
+        }
+        Ok(())
+    }
+
+    /// Enables or disables strict mode. See [`crate::SitemapWriter::strict`].
+    pub fn strict(mut self, strict: bool) -> AsyncSitemapWriter<W> {
+        self.strict = strict;
+        self
+    }
+
+    /// Silently skips news articles published more than two days before
+    /// `now`. See [`crate::SitemapWriter::skip_stale_news`].
+    pub fn skip_stale_news(mut self, now: SystemTime) -> AsyncSitemapWriter<W> {
+        self.news_cutoff = Some(now);
+        self
+    }
+
+    /// Normalizes the `loc` of each URL before writing it, and silently
+    /// skips URLs whose normalized `loc` was already written. See
+    /// [`crate::SitemapWriter::normalize`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{AsyncSitemapWriter, SitemapNormalizer, SitemapUrl};
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+    /// let mut writer = AsyncSitemapWriter::new(Vec::new())
+    ///     .await
+    ///     .unwrap()
+    ///     .normalize(SitemapNormalizer::new());
+    /// writer.push(&SitemapUrl::new("https://Example.com/a")).await.unwrap();
+    /// writer.push(&SitemapUrl::new("https://example.com/a?utm_source=x")).await.unwrap();
+    /// assert_eq!(writer.url_count(), 1);
+    /// # });
+    /// ```
+    pub fn normalize(mut self, normalizer: SitemapNormalizer) -> AsyncSitemapWriter<W> {
+        self.dedup = Some(SitemapDedup::new(normalizer));
+        self
+    }
+
+    /// Writes the closing `</urlset>` tag, flushes and returns the underlying
+    /// writer.
+    pub async fn finish(mut self) -> Result<W, SitemapError> {
+        let close = self.format.line(URLSET_CLOSE, 0);
+        self.write_text(&close).await?;
+        match self.writer.flush().await {
+            Ok(_) => Ok(self.writer),
+            Err(e) => Err(SitemapError::Write(e.to_string())),
+        }
+    }
+
+    /// Returns the number of `<url>` entries written so far.
+    pub fn url_count(&self) -> usize {
+        self.url_count
+    }
+
+    /// Returns the number of bytes written so far, including the header.
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    async fn write_text(&mut self, str: &str) -> Result<(), SitemapError> {
+        if let Err(e) = self.writer.write_all(str.as_bytes()).await {
+            return Err(SitemapError::Write(e.to_string()));
+        }
+        self.bytes_written += str.len();
+        Ok(())
+    }
+}
+
+impl AsyncSitemapWriter {
+    /// Creates a sitemap XML file at the specified path without blocking the
+    /// runtime.
+    ///
+    /// The async counterpart of [`crate::SitemapWriter::make`]. The required
+    /// extension namespaces are detected from `urls`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::{AsyncSitemapWriter, SitemapUrl};
+    ///
+    /// # async fn run() {
+    /// let result = AsyncSitemapWriter::make("sitemap.xml", vec![
+    ///     SitemapUrl::new("https://example.com/"),
+    /// ]).await;
+    /// assert!(result.is_ok());
+    /// # }
+    /// ```
+    pub async fn make(path: &str, urls: Vec<SitemapUrl>) -> Result<(), SitemapError> {
+        let (file, temp) = create(path).await?;
+        let file = async {
+            let namespaces = SitemapNamespaces::from_urls(&urls);
+            let mut writer = AsyncSitemapWriter::with_namespaces(file, namespaces).await?;
+            for url in &urls {
+                writer.push(url).await?;
+            }
+            writer.finish().await
+        };
+        close(file.await, temp).await
+    }
+
+    /// Creates a sitemap XML file at the specified path from a stream of URLs.
+    ///
+    /// Since a stream cannot be inspected up front, the extension namespaces
+    /// used by its entries must be declared.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file path where the sitemap will be written.
+    /// * `namespaces` - The extension namespaces used by the entries.
+    /// * `urls` - A [`futures::Stream`] of [`SitemapUrl`].
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of URLs written.
+    pub async fn make_stream<S>(
+        path: &str,
+        namespaces: SitemapNamespaces,
+        urls: S,
+    ) -> Result<usize, SitemapError>
+    where
+        S: Stream<Item = SitemapUrl>,
+    {
+        let (file, temp) = create(path).await?;
+        let mut count = 0;
+        let file = async {
+            let mut writer = AsyncSitemapWriter::with_namespaces(file, namespaces).await?;
+            writer.push_all(urls).await?;
+            count = writer.url_count();
+            writer.finish().await
+        };
+        close(file.await, temp).await?;
+        Ok(count)
+    }
+}
+
+/// An asynchronous writer for generating XML sitemap index files.
+///
+/// The async counterpart of [`crate::SitemapIndexWriter`].
+///
+/// Requires the `tokio` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use futures::stream;
+/// use sitemap_writer::{AsyncSitemapIndexWriter, SitemapIndex};
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let sitemaps = stream::iter(vec![
+///     SitemapIndex::new("https://example.com/sitemap1.xml"),
+///     SitemapIndex::new("https://example.com/sitemap2.xml"),
+/// ]);
+///
+/// let mut writer = AsyncSitemapIndexWriter::new(Vec::new()).await.unwrap();
+/// writer.push_all(sitemaps).await.unwrap();
+/// let xml = String::from_utf8(writer.finish().await.unwrap()).unwrap();
+/// assert!(xml.contains("<loc>https://example.com/sitemap2.xml</loc>"));
+/// # });
+/// ```
+pub struct AsyncSitemapIndexWriter<W: AsyncWrite + Unpin = File> {
+    writer: W,
+    format: SitemapFormat,
+    strict: bool,
+    sitemap_count: usize,
+    bytes_written: usize,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncSitemapIndexWriter<W> {
+    /// Starts a new sitemap index on the given writer.
+    ///
+    /// The XML declaration and the opening `<sitemapindex>` tag are written
+    /// immediately.
+    pub async fn new(writer: W) -> Result<AsyncSitemapIndexWriter<W>, SitemapError> {
+        AsyncSitemapIndexWriter::with_format(writer, SitemapFormat::default()).await
+    }
+
+    /// Starts a new sitemap index on the given writer, laying out the XML
+    /// with the given format.
+    pub async fn with_format(
+        writer: W,
+        format: SitemapFormat,
+    ) -> Result<AsyncSitemapIndexWriter<W>, SitemapError> {
+        let mut index = AsyncSitemapIndexWriter {
+            writer,
+            format,
+            strict: false,
+            sitemap_count: 0,
+            bytes_written: 0,
+        };
+        index.write_text(&format.line(XML_HEADER, 0)).await?;
+        index.write_text(&format.line(SITEMAPINDEX_OPEN, 0)).await?;
+        Ok(index)
+    }
+
+    /// Writes a single `<sitemap>` entry.
+    ///
+    /// In strict mode, returns a [`SitemapError::InvalidEntry`] without
+    /// writing anything if the entry is invalid.
+    pub async fn push(&mut self, sitemap: &SitemapIndex) -> Result<(), SitemapError> {
+        if self.strict {
+            check_valid_sitemap(sitemap, self.sitemap_count)?;
+        }
+        let row = render_sitemap(sitemap);
+        let row = self.format.element(&row, 1).into_owned();
+        self.write_text(&row).await?;
+        self.sitemap_count += 1;
+        Ok(())
+    }
+
+    /// Writes every entry of a stream, stopping at the first error.
+    pub async fn push_all<S>(&mut self, sitemaps: S) -> Result<(), SitemapError>
+    where
+        S: Stream<Item = SitemapIndex>,
+    {
+        let mut sitemaps = std::pin::pin!(sitemaps);
+        while let Some(sitemap) = sitemaps.next().await {
+            self.push(&sitemap).await?;
+        }
+        Ok(())
+    }
+
+    /// Enables or disables strict mode. See
+    /// [`crate::SitemapIndexWriter::strict`].
+    pub fn strict(mut self, strict: bool) -> AsyncSitemapIndexWriter<W> {
+        self.strict = strict;
+        self
+    }
+
+    /// Writes the closing `</sitemapindex>` tag, flushes and returns the
+    /// underlying writer.
+    pub async fn finish(mut self) -> Result<W, SitemapError> {
+        let close = self.format.line(SITEMAPINDEX_CLOSE, 0);
+        self.write_text(&close).await?;
+        match self.writer.flush().await {
+            Ok(_) => Ok(self.writer),
+            Err(e) => Err(SitemapError::Write(e.to_string())),
+        }
+    }
+
+    /// Returns the number of `<sitemap>` entries written so far.
+    pub fn sitemap_count(&self) -> usize {
+        self.sitemap_count
+    }
+
+    /// Returns the number of bytes written so far, including the header.
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    async fn write_text(&mut self, str: &str) -> Result<(), SitemapError> {
+        if let Err(e) = self.writer.write_all(str.as_bytes()).await {
+            return Err(SitemapError::Write(e.to_string()));
+        }
+        self.bytes_written += str.len();
+        Ok(())
+    }
+}
+
+impl AsyncSitemapIndexWriter {
+    /// Creates a sitemap index XML file at the specified path without
+    /// blocking the runtime.
+    ///
+    /// The async counterpart of [`crate::SitemapIndexWriter::make`].
+    pub async fn make(path: &str, sitemaps: Vec<SitemapIndex>) -> Result<(), SitemapError> {
+        let (file, temp) = create(path).await?;
+        let file = async {
+            let mut writer = AsyncSitemapIndexWriter::new(file).await?;
+            for sitemap in &sitemaps {
+                writer.push(sitemap).await?;
+            }
+            writer.finish().await
+        };
+        close(file.await, temp).await
+    }
+}
+
+/// Creates a temporary file next to `path`, renamed over it by [`close`].
+async fn create(path: &str) -> Result<(BufWriter<File>, TempFile), SitemapError> {
+    let temp = TempFile::new(Path::new(path));
+    match OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(temp.path())
+        .await
+    {
+        Ok(file) => Ok((BufWriter::new(file), temp)),
+        Err(e) => {
+            // Nothing was created, so there is nothing to remove.
+            temp.keep();
+            Err(SitemapError::FileOpen(e.to_string()))
+        }
+    }
+}
+
+/// Persists the temporary file once it was written, or removes it if writing
+/// failed.
+///
+/// The removal is awaited here rather than left to the blocking `Drop` of
+/// [`TempFile`], which only runs if the future is dropped before this.
+async fn close(
+    file: Result<BufWriter<File>, SitemapError>,
+    temp: TempFile,
+) -> Result<(), SitemapError> {
+    let result = match file {
+        Ok(file) => persist(file, &temp).await,
+        Err(e) => Err(e),
+    };
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(temp.path()).await;
+    }
+    temp.keep();
+    result
+}
+
+/// Flushes the buffer, waits until the data reached the disk, renames the
+/// temporary file over the target and syncs the directory.
+async fn persist(mut file: BufWriter<File>, temp: &TempFile) -> Result<(), SitemapError> {
+    let write_error = |e: std::io::Error| SitemapError::Write(e.to_string());
+    file.shutdown().await.map_err(write_error)?;
+    file.into_inner().sync_all().await.map_err(write_error)?;
+    tokio::fs::rename(temp.path(), temp.target())
+        .await
+        .map_err(write_error)?;
+    // Directories cannot be opened as files on Windows.
+    if cfg!(unix) {
+        File::open(parent_dir(temp.target()))
+            .await
+            .map_err(write_error)?
+            .sync_all()
+            .await
+            .map_err(write_error)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,588 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::SitemapError;
+
+/// The precision of a [`SitemapDateTime`], one of the levels allowed by the
+/// W3C Datetime profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SitemapDateTimePrecision {
+    /// `YYYY`
+    YEAR,
+    /// `YYYY-MM`
+    MONTH,
+    /// `YYYY-MM-DD`
+    DAY,
+    /// `YYYY-MM-DDThh:mmTZD`
+    MINUTE,
+    /// `YYYY-MM-DDThh:mm:ssTZD`
+    SECOND,
+    /// `YYYY-MM-DDThh:mm:ss.sTZD`
+    FRACTION,
+}
+
+/// A date or date and time in the W3C Datetime format used by `lastmod`.
+///
+/// Values keep the precision they were created with, so `2024-01` is
+/// written back as `2024-01`. Values with a time always carry a timezone
+/// offset; UTC is written as `+00:00`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use sitemap_writer::{SitemapDateTime, SitemapDateTimePrecision};
+///
+/// let date: SitemapDateTime = "2024-01-15T10:30:00+09:00".parse().unwrap();
+/// assert_eq!(date.precision(), SitemapDateTimePrecision::SECOND);
+/// assert_eq!(date.to_string(), "2024-01-15T10:30:00+09:00");
+///
+/// let now = SitemapDateTime::from(UNIX_EPOCH + Duration::from_secs(1_705_000_000));
+/// assert_eq!(now.to_string(), "2024-01-11T19:06:40+00:00");
+///
+/// let day = SitemapDateTime::date(2024, 2, 29).unwrap();
+/// assert_eq!(day.to_string(), "2024-02-29");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SitemapDateTime {
+    year: i32,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanosecond: u32,
+    /// The timezone offset in minutes east of UTC.
+    offset: i16,
+    precision: SitemapDateTimePrecision,
+}
+
+impl SitemapDateTime {
+    /// Creates a date without a time (`YYYY-MM-DD`).
+    ///
+    /// # Arguments
+    ///
+    /// * `year` - The year, from 0 to 9999.
+    /// * `month` - The month, from 1 to 12.
+    /// * `day` - The day of the month.
+    ///
+    /// # Returns
+    ///
+    /// A [`SitemapError::InvalidValue`] if the date does not exist.
+    pub fn date(year: i32, month: u8, day: u8) -> Result<SitemapDateTime, SitemapError> {
+        if !(0..=9999).contains(&year)
+            || !(1..=12).contains(&month)
+            || day < 1
+            || day > days_in_month(year as i64, month as i64) as u8
+        {
+            return Err(SitemapError::InvalidValue(format!(
+                "invalid date: {:04}-{:02}-{:02}",
+                year, month, day
+            )));
+        }
+        Ok(SitemapDateTime {
+            year,
+            month,
+            day,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            nanosecond: 0,
+            offset: 0,
+            precision: SitemapDateTimePrecision::DAY,
+        })
+    }
+
+    /// Returns the precision the value was created with.
+    pub fn precision(&self) -> SitemapDateTimePrecision {
+        self.precision
+    }
+
+    /// Returns the number of seconds since the Unix epoch.
+    ///
+    /// Values without a time are interpreted as the start of the period in
+    /// UTC.
+    pub fn timestamp(&self) -> i64 {
+        let days = days_from_civil(self.year as i64, self.month as i64, self.day as i64);
+        days * 86_400 + self.hour as i64 * 3_600 + self.minute as i64 * 60 + self.second as i64
+            - self.offset as i64 * 60
+    }
+
+    /// Formats as an RFC 3339 date and time with at least second precision,
+    /// as required by Atom.
+    ///
+    /// Values without a time are the start of the period in UTC.
+    pub(crate) fn to_rfc3339(self) -> String {
+        let mut date_time = self;
+        if date_time.precision < SitemapDateTimePrecision::SECOND {
+            date_time.precision = SitemapDateTimePrecision::SECOND;
+        }
+        date_time.to_string()
+    }
+
+    /// Formats as an RFC 2822 date and time, as required by RSS 2.0.
+    pub(crate) fn to_rfc2822(self) -> String {
+        // 1970-01-01 was a Thursday.
+        const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+        const MONTHS: [&str; 12] = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ];
+        let days = days_from_civil(self.year as i64, self.month as i64, self.day as i64);
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let offset = self.offset.unsigned_abs();
+        format!(
+            "{}, {:02} {} {:04} {:02}:{:02}:{:02} {}{:02}{:02}",
+            WEEKDAYS[days.rem_euclid(7) as usize],
+            self.day,
+            MONTHS[self.month as usize - 1],
+            self.year,
+            self.hour,
+            self.minute,
+            self.second,
+            sign,
+            offset / 60,
+            offset % 60
+        )
+    }
+}
+
+impl Display for SitemapDateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}", self.year)?;
+        if self.precision == SitemapDateTimePrecision::YEAR {
+            return Ok(());
+        }
+        write!(f, "-{:02}", self.month)?;
+        if self.precision == SitemapDateTimePrecision::MONTH {
+            return Ok(());
+        }
+        write!(f, "-{:02}", self.day)?;
+        if self.precision == SitemapDateTimePrecision::DAY {
+            return Ok(());
+        }
+        write!(f, "T{:02}:{:02}", self.hour, self.minute)?;
+        if self.precision >= SitemapDateTimePrecision::SECOND {
+            write!(f, ":{:02}", self.second)?;
+        }
+        if self.precision == SitemapDateTimePrecision::FRACTION {
+            let fraction = format!("{:09}", self.nanosecond);
+            let fraction = fraction.trim_end_matches('0');
+            write!(f, ".{}", if fraction.is_empty() { "0" } else { fraction })?;
+        }
+        let sign = if self.offset < 0 { '-' } else { '+' };
+        let offset = self.offset.unsigned_abs();
+        write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)
+    }
+}
+
+impl FromStr for SitemapDateTime {
+    type Err = SitemapError;
+
+    /// Parses any precision allowed by the W3C Datetime profile.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s).ok_or_else(|| SitemapError::InvalidValue(format!("invalid W3C Datetime: {}", s)))
+    }
+}
+
+impl From<SystemTime> for SitemapDateTime {
+    /// Converts to a UTC date and time with second precision.
+    fn from(time: SystemTime) -> Self {
+        let timestamp = system_time_to_timestamp(time);
+        let (year, month, day) = civil_from_days(timestamp.div_euclid(86_400));
+        let seconds = timestamp.rem_euclid(86_400);
+        SitemapDateTime {
+            year: year as i32,
+            month: month as u8,
+            day: day as u8,
+            hour: (seconds / 3_600) as u8,
+            minute: (seconds / 60 % 60) as u8,
+            second: (seconds % 60) as u8,
+            nanosecond: 0,
+            offset: 0,
+            precision: SitemapDateTimePrecision::SECOND,
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for SitemapDateTime {
+    /// Converts keeping the timezone offset, with second precision unless
+    /// the value has a fractional second.
+    fn from(value: chrono::DateTime<Tz>) -> Self {
+        use chrono::{Datelike, Offset, Timelike};
+
+        let offset = value.offset().fix().local_minus_utc();
+        let local = value.naive_local();
+        let nanosecond = local.nanosecond().min(999_999_999);
+        SitemapDateTime {
+            year: local.year(),
+            month: local.month() as u8,
+            day: local.day() as u8,
+            hour: local.hour() as u8,
+            minute: local.minute() as u8,
+            second: local.second() as u8,
+            nanosecond,
+            offset: (offset / 60) as i16,
+            precision: time_precision(nanosecond),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDate> for SitemapDateTime {
+    /// Converts to a date without a time.
+    fn from(value: chrono::NaiveDate) -> Self {
+        use chrono::Datelike;
+
+        SitemapDateTime {
+            year: value.year(),
+            month: value.month() as u8,
+            day: value.day() as u8,
+            precision: SitemapDateTimePrecision::DAY,
+            ..SitemapDateTime::from(UNIX_EPOCH)
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for SitemapDateTime {
+    /// Converts keeping the timezone offset, with second precision unless
+    /// the value has a fractional second.
+    fn from(value: time::OffsetDateTime) -> Self {
+        SitemapDateTime {
+            year: value.year(),
+            month: u8::from(value.month()),
+            day: value.day(),
+            hour: value.hour(),
+            minute: value.minute(),
+            second: value.second(),
+            nanosecond: value.nanosecond(),
+            offset: (value.offset().whole_seconds() / 60) as i16,
+            precision: time_precision(value.nanosecond()),
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::Date> for SitemapDateTime {
+    /// Converts to a date without a time.
+    fn from(value: time::Date) -> Self {
+        SitemapDateTime {
+            year: value.year(),
+            month: u8::from(value.month()),
+            day: value.day(),
+            precision: SitemapDateTimePrecision::DAY,
+            ..SitemapDateTime::from(UNIX_EPOCH)
+        }
+    }
+}
+
+/// A typed `lastmod` value, accepted by [`crate::SitemapUrl::set_lastmod`]
+/// and returned by [`crate::SitemapUrl::parsed_lastmod`].
+///
+/// Converting from a string parses it as a [`SitemapDateTime`]; strings that
+/// are not valid W3C Datetimes are kept as [`SitemapLastmod::Raw`]. Raw
+/// values are reported by validation and refused in strict mode.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::SystemTime;
+/// use sitemap_writer::{SitemapDateTime, SitemapLastmod, SitemapUrl};
+///
+/// let mut url = SitemapUrl::new("https://example.com/");
+/// url.lastmod = Some("2024-01-15".to_string());
+/// assert!(matches!(url.parsed_lastmod(), Some(SitemapLastmod::DateTime(_))));
+///
+/// url.set_lastmod(SystemTime::now());
+/// url.set_lastmod(SitemapDateTime::date(2024, 1, 15).unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SitemapLastmod {
+    /// A valid W3C Datetime.
+    DateTime(SitemapDateTime),
+    /// A string that could not be parsed, written as given.
+    Raw(String),
+}
+
+impl SitemapLastmod {
+    /// Returns the parsed date, or `None` for a raw string.
+    pub fn date_time(&self) -> Option<&SitemapDateTime> {
+        match self {
+            SitemapLastmod::DateTime(date_time) => Some(date_time),
+            SitemapLastmod::Raw(_) => None,
+        }
+    }
+
+    /// Returns the number of seconds since the Unix epoch, or `None` for a
+    /// raw string.
+    pub fn timestamp(&self) -> Option<i64> {
+        self.date_time().map(SitemapDateTime::timestamp)
+    }
+}
+
+impl Display for SitemapLastmod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SitemapLastmod::DateTime(date_time) => write!(f, "{}", date_time),
+            SitemapLastmod::Raw(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+impl From<&str> for SitemapLastmod {
+    fn from(value: &str) -> Self {
+        match parse(value) {
+            Some(date_time) => SitemapLastmod::DateTime(date_time),
+            None => SitemapLastmod::Raw(value.to_string()),
+        }
+    }
+}
+
+impl From<String> for SitemapLastmod {
+    fn from(value: String) -> Self {
+        match parse(&value) {
+            Some(date_time) => SitemapLastmod::DateTime(date_time),
+            None => SitemapLastmod::Raw(value),
+        }
+    }
+}
+
+impl From<SitemapDateTime> for SitemapLastmod {
+    fn from(value: SitemapDateTime) -> Self {
+        SitemapLastmod::DateTime(value)
+    }
+}
+
+impl From<SystemTime> for SitemapLastmod {
+    fn from(value: SystemTime) -> Self {
+        SitemapLastmod::DateTime(value.into())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for SitemapLastmod {
+    fn from(value: chrono::DateTime<Tz>) -> Self {
+        SitemapLastmod::DateTime(value.into())
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<chrono::NaiveDate> for SitemapLastmod {
+    fn from(value: chrono::NaiveDate) -> Self {
+        SitemapLastmod::DateTime(value.into())
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::OffsetDateTime> for SitemapLastmod {
+    fn from(value: time::OffsetDateTime) -> Self {
+        SitemapLastmod::DateTime(value.into())
+    }
+}
+
+#[cfg(feature = "time")]
+impl From<time::Date> for SitemapLastmod {
+    fn from(value: time::Date) -> Self {
+        SitemapLastmod::DateTime(value.into())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SitemapDateTime {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SitemapDateTime {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SitemapLastmod {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for SitemapLastmod {
+    /// Deserializes from a string; like `From<String>`, values that are not
+    /// W3C Datetimes are kept as [`SitemapLastmod::Raw`].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(SitemapLastmod::from)
+    }
+}
+
+/// Parses a W3C Datetime string into seconds since the Unix epoch.
+///
+/// Values without a time are interpreted as the start of the period in UTC.
+pub(crate) fn parse_timestamp(value: &str) -> Option<i64> {
+    parse(value).map(|date_time| date_time.timestamp())
+}
+
+/// Parses a W3C Datetime string.
+///
+/// Accepts every precision allowed by the profile (`YYYY`, `YYYY-MM`,
+/// `YYYY-MM-DD` and date with time and timezone).
+fn parse(value: &str) -> Option<SitemapDateTime> {
+    let (date, time) = match value.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (value, None),
+    };
+
+    let mut parts = date.split('-');
+    let year = parse_digits(parts.next()?, 4)?;
+    let mut precision = SitemapDateTimePrecision::YEAR;
+    let month = match parts.next() {
+        Some(month) => {
+            precision = SitemapDateTimePrecision::MONTH;
+            parse_digits(month, 2)?
+        }
+        None => 1,
+    };
+    let day = match parts.next() {
+        Some(day) => {
+            precision = SitemapDateTimePrecision::DAY;
+            parse_digits(day, 2)?
+        }
+        None => 1,
+    };
+    if parts.next().is_some() || !(1..=12).contains(&month) {
+        return None;
+    }
+    if day < 1 || day > days_in_month(year, month) {
+        return None;
+    }
+    let mut date_time = SitemapDateTime {
+        year: year as i32,
+        month: month as u8,
+        day: day as u8,
+        hour: 0,
+        minute: 0,
+        second: 0,
+        nanosecond: 0,
+        offset: 0,
+        precision,
+    };
+
+    if let Some(time) = time {
+        // A time is only allowed together with a full date.
+        if precision != SitemapDateTimePrecision::DAY {
+            return None;
+        }
+        let (clock, offset) = split_timezone(time)?;
+        let mut fields = clock.split(':');
+        let hour = parse_digits(fields.next()?, 2)?;
+        let minute = parse_digits(fields.next()?, 2)?;
+        date_time.precision = SitemapDateTimePrecision::MINUTE;
+        if let Some(second) = fields.next() {
+            date_time.precision = SitemapDateTimePrecision::SECOND;
+            let whole = match second.split_once('.') {
+                Some((whole, fraction)) => {
+                    if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                        return None;
+                    }
+                    date_time.precision = SitemapDateTimePrecision::FRACTION;
+                    date_time.nanosecond = format!("{:0<9.9}", fraction).parse().ok()?;
+                    whole
+                }
+                None => second,
+            };
+            date_time.second = parse_digits(whole, 2)? as u8;
+        }
+        if fields.next().is_some() || hour > 23 || minute > 59 || date_time.second > 59 {
+            return None;
+        }
+        date_time.hour = hour as u8;
+        date_time.minute = minute as u8;
+        date_time.offset = offset;
+    }
+    Some(date_time)
+}
+
+/// Returns the number of seconds since the Unix epoch for a `SystemTime`.
+pub(crate) fn system_time_to_timestamp(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
+
+/// Splits `hh:mm:ssTZD` into the clock part and the offset in minutes.
+fn split_timezone(time: &str) -> Option<(&str, i16)> {
+    if let Some(clock) = time.strip_suffix('Z') {
+        return Some((clock, 0));
+    }
+    let index = time.rfind(['+', '-'])?;
+    let (clock, zone) = time.split_at(index);
+    let sign = if zone.starts_with('-') { -1 } else { 1 };
+    let (hours, minutes) = zone[1..].split_once(':')?;
+    let hours = parse_digits(hours, 2)?;
+    let minutes = parse_digits(minutes, 2)?;
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some((clock, sign * (hours * 60 + minutes) as i16))
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn time_precision(nanosecond: u32) -> SitemapDateTimePrecision {
+    if nanosecond == 0 {
+        SitemapDateTimePrecision::SECOND
+    } else {
+        SitemapDateTimePrecision::FRACTION
+    }
+}
+
+fn parse_digits(value: &str, len: usize) -> Option<i64> {
+    if value.len() != len || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the proleptic Gregorian date for a number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Returns the number of days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
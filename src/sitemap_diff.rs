@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::error::SitemapError;
+use crate::sitemap_reader::SitemapReader;
+use crate::sitemap_url::SitemapUrl;
+
+/// A field of [`SitemapUrl`] compared by [`SitemapDiff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SitemapField {
+    /// `lastmod`
+    LASTMOD,
+    /// `changefreq`
+    CHANGEFREQ,
+    /// `priority`
+    PRIORITY,
+}
+
+impl Display for SitemapField {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SitemapField::LASTMOD => "lastmod",
+            SitemapField::CHANGEFREQ => "changefreq",
+            SitemapField::PRIORITY => "priority",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A URL present in both versions whose fields differ.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SitemapUrlChange {
+    /// The URL in the old version.
+    pub old: SitemapUrl,
+
+    /// The URL in the new version.
+    pub new: SitemapUrl,
+
+    /// The fields that differ, in declaration order.
+    pub fields: Vec<SitemapField>,
+}
+
+/// The changes between two versions of a sitemap.
+///
+/// URLs are matched by `loc`. Only `lastmod`, `changefreq` and `priority`
+/// are compared; extensions such as images are not. If a `loc` appears more
+/// than once in a version, its first occurrence is used.
+///
+/// [`Display`] renders a human-readable report, and [`SitemapDiff::to_json`]
+/// a JSON one.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapDiff, SitemapField, SitemapUrl};
+///
+/// let mut page = SitemapUrl::new("https://example.com/page");
+/// let old = vec![page.clone(), SitemapUrl::new("https://example.com/old")];
+/// page.lastmod = Some("2024-02-01".to_string());
+/// let new = vec![page, SitemapUrl::new("https://example.com/new")];
+///
+/// let diff = SitemapDiff::new(&old, &new);
+/// assert_eq!(diff.added[0].loc, "https://example.com/new");
+/// assert_eq!(diff.removed[0].loc, "https://example.com/old");
+/// assert_eq!(diff.changed[0].fields, vec![SitemapField::LASTMOD]);
+/// assert_eq!(
+///     diff.to_string(),
+///     "+ https://example.com/new\n\
+///      - https://example.com/old\n\
+///      ~ https://example.com/page\n    lastmod: (none) -> 2024-02-01\n\
+///      1 added, 1 removed, 1 changed\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SitemapDiff {
+    /// URLs only in the new version, in the order of the new version.
+    pub added: Vec<SitemapUrl>,
+
+    /// URLs only in the old version, in the order of the old version.
+    pub removed: Vec<SitemapUrl>,
+
+    /// URLs in both versions with different fields, in the order of the new
+    /// version.
+    pub changed: Vec<SitemapUrlChange>,
+}
+
+impl SitemapDiff {
+    /// Compares two versions of a sitemap.
+    ///
+    /// # Arguments
+    ///
+    /// * `old` - The URLs of the previous version.
+    /// * `new` - The URLs of the current version.
+    pub fn new(old: &[SitemapUrl], new: &[SitemapUrl]) -> SitemapDiff {
+        let old_by_loc = by_loc(old);
+        let new_by_loc = by_loc(new);
+
+        let mut diff = SitemapDiff::default();
+        for (index, url) in new.iter().enumerate() {
+            if new_by_loc[url.loc.as_str()] != index {
+                continue;
+            }
+            match old_by_loc.get(url.loc.as_str()) {
+                None => diff.added.push(url.clone()),
+                Some(&old_index) => {
+                    let fields = changed_fields(&old[old_index], url);
+                    if !fields.is_empty() {
+                        diff.changed.push(SitemapUrlChange {
+                            old: old[old_index].clone(),
+                            new: url.clone(),
+                            fields,
+                        });
+                    }
+                }
+            }
+        }
+        for (index, url) in old.iter().enumerate() {
+            if old_by_loc[url.loc.as_str()] == index && !new_by_loc.contains_key(url.loc.as_str()) {
+                diff.removed.push(url.clone());
+            }
+        }
+        diff
+    }
+
+    /// Compares two sitemap files.
+    ///
+    /// With the `gzip` feature enabled, files ending in `.gz` are
+    /// decompressed transparently.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::SitemapDiff;
+    ///
+    /// let diff = SitemapDiff::from_files("release/sitemap.xml", "public/sitemap.xml").unwrap();
+    /// if !diff.is_empty() {
+    ///     print!("{}", diff);
+    /// }
+    /// ```
+    pub fn from_files(old_path: &str, new_path: &str) -> Result<SitemapDiff, SitemapError> {
+        let old = SitemapReader::open(old_path)?.read_urls()?;
+        let new = SitemapReader::open(new_path)?.read_urls()?;
+        Ok(SitemapDiff::new(&old, &new))
+    }
+
+    /// Returns `true` if the two versions are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Renders the diff as a JSON report.
+    ///
+    /// Added and removed URLs are listed with their `loc`, `lastmod`,
+    /// `changefreq` and `priority`; changed URLs with the `old` and `new`
+    /// value of each changed field. Missing values are `null`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapDiff, SitemapUrl};
+    ///
+    /// let diff = SitemapDiff::new(&[], &[SitemapUrl::new("https://example.com/")]);
+    /// assert_eq!(
+    ///     diff.to_json(),
+    ///     r#"{"added":[{"loc":"https://example.com/","lastmod":null,"changefreq":null,"priority":null}],"removed":[],"changed":[]}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let urls = |urls: &[SitemapUrl]| -> String {
+            let urls: Vec<String> = urls
+                .iter()
+                .map(|url| {
+                    format!(
+                        r#"{{"loc":{},"lastmod":{},"changefreq":{},"priority":{}}}"#,
+                        json_string(&url.loc),
+                        json_value(url, SitemapField::LASTMOD),
+                        json_value(url, SitemapField::CHANGEFREQ),
+                        json_value(url, SitemapField::PRIORITY),
+                    )
+                })
+                .collect();
+            urls.join(",")
+        };
+        let changed: Vec<String> = self
+            .changed
+            .iter()
+            .map(|change| {
+                let mut json = format!(r#"{{"loc":{}"#, json_string(&change.new.loc));
+                for field in &change.fields {
+                    json += format!(
+                        r#","{}":{{"old":{},"new":{}}}"#,
+                        field,
+                        json_value(&change.old, *field),
+                        json_value(&change.new, *field),
+                    )
+                    .as_str();
+                }
+                json + "}"
+            })
+            .collect();
+        format!(
+            r#"{{"added":[{}],"removed":[{}],"changed":[{}]}}"#,
+            urls(&self.added),
+            urls(&self.removed),
+            changed.join(",")
+        )
+    }
+}
+
+impl Display for SitemapDiff {
+    /// Renders a human-readable report: one line per added (`+`), removed
+    /// (`-`) and changed (`~`) URL, the changed fields indented below, and a
+    /// summary line.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for url in &self.added {
+            writeln!(f, "+ {}", url.loc)?;
+        }
+        for url in &self.removed {
+            writeln!(f, "- {}", url.loc)?;
+        }
+        for change in &self.changed {
+            writeln!(f, "~ {}", change.new.loc)?;
+            for field in &change.fields {
+                writeln!(
+                    f,
+                    "    {}: {} -> {}",
+                    field,
+                    field_value(&change.old, *field).unwrap_or("(none)".to_string()),
+                    field_value(&change.new, *field).unwrap_or("(none)".to_string()),
+                )?;
+            }
+        }
+        writeln!(
+            f,
+            "{} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )
+    }
+}
+
+/// Maps each `loc` to the index of its first occurrence.
+fn by_loc(urls: &[SitemapUrl]) -> HashMap<&str, usize> {
+    let mut map = HashMap::with_capacity(urls.len());
+    for (index, url) in urls.iter().enumerate() {
+        map.entry(url.loc.as_str()).or_insert(index);
+    }
+    map
+}
+
+fn changed_fields(old: &SitemapUrl, new: &SitemapUrl) -> Vec<SitemapField> {
+    let mut fields = vec![];
+    if old.lastmod != new.lastmod {
+        fields.push(SitemapField::LASTMOD);
+    }
+    if old.changefreq != new.changefreq {
+        fields.push(SitemapField::CHANGEFREQ);
+    }
+    if old.priority != new.priority {
+        fields.push(SitemapField::PRIORITY);
+    }
+    fields
+}
+
+fn field_value(url: &SitemapUrl, field: SitemapField) -> Option<String> {
+    match field {
+        SitemapField::LASTMOD => url.lastmod.clone(),
+        SitemapField::CHANGEFREQ => url.changefreq.as_ref().map(ToString::to_string),
+        SitemapField::PRIORITY => url.priority.map(|priority| priority.to_string()),
+    }
+}
+
+fn json_value(url: &SitemapUrl, field: SitemapField) -> String {
+    match (field, field_value(url, field)) {
+        (_, None) => "null".to_string(),
+        // NaN and infinity are not valid JSON numbers.
+        (SitemapField::PRIORITY, Some(priority)) if url.priority.is_some_and(f32::is_finite) => {
+            priority
+        }
+        (_, Some(value)) => json_string(&value),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -0,0 +1,196 @@
+use std::io::Write;
+use std::time::SystemTime;
+
+use crate::error::SitemapError;
+use crate::sitemap_datetime::SitemapDateTime;
+use crate::sitemap_file::SitemapFile;
+use crate::sitemap_format::XML_HEADER;
+use crate::sitemap_url::SitemapUrl;
+
+const ATOM_NS: &str = "http://www.w3.org/2005/Atom";
+
+/// The number of entries a feed holds unless configured otherwise.
+const DEFAULT_FEED_LIMIT: usize = 50;
+
+/// The syndication format written by a [`SitemapFeedWriter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SitemapFeedFormat {
+    /// RSS 2.0.
+    RSS,
+    /// Atom 1.0.
+    ATOM,
+}
+
+/// A writer for "recently updated" RSS 2.0 and Atom 1.0 feeds, which search
+/// engines accept in place of a sitemap.
+///
+/// The feed is generated from the same [`SitemapUrl`] values as a sitemap:
+/// `loc` becomes the entry link (and title), and `lastmod` its updated date.
+/// Entries are sorted newest first and truncated to the configured limit
+/// (50 by default). URLs without a valid `lastmod` are left out, since they
+/// cannot be ordered.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapFeedFormat, SitemapFeedWriter, SitemapUrl};
+///
+/// let mut old = SitemapUrl::new("https://example.com/blog/old");
+/// old.lastmod = Some("2024-01-01".to_string());
+/// let mut new = SitemapUrl::new("https://example.com/blog/new");
+/// new.lastmod = Some("2024-02-01T09:00:00+09:00".to_string());
+///
+/// let feed = SitemapFeedWriter::new(SitemapFeedFormat::ATOM, "Example Blog", "https://example.com/")
+///     .with_limit(10)
+///     .build(vec![old, new]);
+/// assert!(feed.contains("<updated>2024-02-01T09:00:00+09:00</updated>"));
+/// assert!(feed.find("blog/new").unwrap() < feed.find("blog/old").unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SitemapFeedWriter {
+    format: SitemapFeedFormat,
+    title: String,
+    link: String,
+    description: Option<String>,
+    limit: usize,
+}
+
+impl SitemapFeedWriter {
+    /// Creates a new feed writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - Whether to write RSS 2.0 or Atom 1.0.
+    /// * `title` - The title of the feed.
+    /// * `link` - The URL of the site the feed belongs to. Atom also uses it
+    ///   as the feed `id`.
+    pub fn new(format: SitemapFeedFormat, title: &str, link: &str) -> SitemapFeedWriter {
+        SitemapFeedWriter {
+            format,
+            title: title.to_string(),
+            link: link.to_string(),
+            description: None,
+            limit: DEFAULT_FEED_LIMIT,
+        }
+    }
+
+    /// Sets the feed description (RSS `description`, Atom `subtitle`).
+    ///
+    /// RSS requires a description; the title is used when none is set.
+    pub fn with_description(mut self, description: &str) -> SitemapFeedWriter {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the maximum number of entries in the feed.
+    pub fn with_limit(mut self, limit: usize) -> SitemapFeedWriter {
+        self.limit = limit;
+        self
+    }
+
+    /// Creates a feed file at the specified path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::{SitemapFeedFormat, SitemapFeedWriter, SitemapUrl};
+    ///
+    /// let mut url = SitemapUrl::new("https://example.com/blog/post");
+    /// url.lastmod = Some("2024-01-15".to_string());
+    ///
+    /// let result = SitemapFeedWriter::new(SitemapFeedFormat::RSS, "Example Blog", "https://example.com/")
+    ///     .make("feed.xml", vec![url]);
+    /// assert!(result.is_ok());
+    /// ```
+    pub fn make(&self, path: &str, urls: Vec<SitemapUrl>) -> Result<(), SitemapError> {
+        let mut file = SitemapFile::create(path)?;
+        if let Err(e) = file.write_all(self.build(urls).as_bytes()) {
+            return Err(SitemapError::Write(e.to_string()));
+        }
+        file.close()
+    }
+
+    /// Builds the feed XML string from the provided URLs.
+    pub fn build(&self, urls: Vec<SitemapUrl>) -> String {
+        let mut entries: Vec<(SitemapDateTime, String)> = urls
+            .into_iter()
+            .filter_map(|url| {
+                let lastmod = url.parsed_lastmod()?;
+                let updated = *lastmod.date_time()?;
+                Some((updated, url.loc))
+            })
+            .collect();
+        entries.sort_by_key(|(updated, _)| std::cmp::Reverse(updated.timestamp()));
+        entries.truncate(self.limit);
+
+        match self.format {
+            SitemapFeedFormat::RSS => self.build_rss(&entries),
+            SitemapFeedFormat::ATOM => self.build_atom(&entries),
+        }
+    }
+
+    fn build_rss(&self, entries: &[(SitemapDateTime, String)]) -> String {
+        let mut content = XML_HEADER.to_string();
+        content += r#"<rss version="2.0"><channel>"#;
+        content += format!("<title>{}</title>", html_escape::encode_text(&self.title)).as_str();
+        content += format!("<link>{}</link>", html_escape::encode_text(&self.link)).as_str();
+        content += format!(
+            "<description>{}</description>",
+            html_escape::encode_text(self.description.as_ref().unwrap_or(&self.title))
+        )
+        .as_str();
+        if let Some((updated, _)) = entries.first() {
+            content += format!("<lastBuildDate>{}</lastBuildDate>", updated.to_rfc2822()).as_str();
+        }
+        for (updated, loc) in entries {
+            let loc = html_escape::encode_text(loc);
+            content += "<item>";
+            content += format!("<title>{}</title>", loc).as_str();
+            content += format!("<link>{}</link>", loc).as_str();
+            content += format!("<guid>{}</guid>", loc).as_str();
+            content += format!("<pubDate>{}</pubDate>", updated.to_rfc2822()).as_str();
+            content += "</item>";
+        }
+        content += "</channel></rss>";
+        content
+    }
+
+    fn build_atom(&self, entries: &[(SitemapDateTime, String)]) -> String {
+        // Atom requires an updated date even for an empty feed.
+        let updated = match entries.first() {
+            Some((updated, _)) => *updated,
+            None => SitemapDateTime::from(SystemTime::now()),
+        };
+        let mut content = XML_HEADER.to_string();
+        content += format!(r#"<feed xmlns="{}">"#, ATOM_NS).as_str();
+        content += format!("<title>{}</title>", html_escape::encode_text(&self.title)).as_str();
+        if let Some(description) = &self.description {
+            content += format!(
+                "<subtitle>{}</subtitle>",
+                html_escape::encode_text(description)
+            )
+            .as_str();
+        }
+        content += format!(
+            r#"<link href="{}"/>"#,
+            html_escape::encode_double_quoted_attribute(&self.link)
+        )
+        .as_str();
+        content += format!("<id>{}</id>", html_escape::encode_text(&self.link)).as_str();
+        content += format!("<updated>{}</updated>", updated.to_rfc3339()).as_str();
+        for (updated, loc) in entries {
+            content += "<entry>";
+            content += format!("<title>{}</title>", html_escape::encode_text(loc)).as_str();
+            content += format!(
+                r#"<link href="{}"/>"#,
+                html_escape::encode_double_quoted_attribute(loc)
+            )
+            .as_str();
+            content += format!("<id>{}</id>", html_escape::encode_text(loc)).as_str();
+            content += format!("<updated>{}</updated>", updated.to_rfc3339()).as_str();
+            content += "</entry>";
+        }
+        content += "</feed>";
+        content
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(feature = "gzip")]
+use flate2::{Compression, write::GzEncoder};
+
+use crate::error::SitemapError;
+
+/// Distinguishes temporary files created by the same process.
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// A buffered output file, optionally gzip-compressed, that replaces its
+/// target atomically.
+///
+/// Data is written to a hidden temporary file in the directory of the
+/// target. [`SitemapFile::close`] syncs it to disk, renames it over the
+/// target and syncs the directory, so readers never see a partially written
+/// file and the rename survives a crash. If writing fails,
+/// or the `SitemapFile` is dropped without being closed, the temporary file
+/// is removed and the previous version of the target is left untouched.
+///
+/// Every `make` function writes through a `SitemapFile`. Use it directly to
+/// stream to a file with [`crate::SitemapWriter`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sitemap_writer::{SitemapFile, SitemapUrl, SitemapWriter};
+///
+/// let mut writer = SitemapWriter::new(SitemapFile::create("sitemap.xml").unwrap()).unwrap();
+/// writer.push(&SitemapUrl::new("https://example.com/")).unwrap();
+/// writer.finish().unwrap().close().unwrap();
+/// ```
+pub struct SitemapFile {
+    writer: OutputWriter,
+    temp: TempFile,
+}
+
+enum OutputWriter {
+    Plain(BufWriter<File>),
+    #[cfg(feature = "gzip")]
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl SitemapFile {
+    /// Creates a plain file that replaces `path` on close.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`SitemapError::FileOpen`] if the temporary file cannot be
+    /// created, e.g. because the directory does not exist.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<SitemapFile, SitemapError> {
+        let (file, temp) = open(path.as_ref())?;
+        Ok(SitemapFile {
+            writer: OutputWriter::Plain(BufWriter::new(file)),
+            temp,
+        })
+    }
+
+    /// Creates a gzip-compressed file that replaces `path` on close.
+    ///
+    /// Requires the `gzip` feature.
+    #[cfg(feature = "gzip")]
+    pub fn create_gz<P: AsRef<Path>>(path: P) -> Result<SitemapFile, SitemapError> {
+        let (file, temp) = open(path.as_ref())?;
+        let encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+        Ok(SitemapFile {
+            writer: OutputWriter::Gzip(encoder),
+            temp,
+        })
+    }
+
+    /// Writes any pending data, including the gzip trailer, syncs it to disk,
+    /// renames the temporary file over the target and syncs the directory of
+    /// the target.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`SitemapError::Write`] if any step fails; the temporary
+    /// file is then removed.
+    pub fn close(self) -> Result<(), SitemapError> {
+        let file: std::io::Result<BufWriter<File>> = match self.writer {
+            OutputWriter::Plain(file) => Ok(file),
+            #[cfg(feature = "gzip")]
+            OutputWriter::Gzip(encoder) => encoder.finish(),
+        };
+        let file = match file {
+            Ok(file) => file,
+            Err(e) => return Err(SitemapError::Write(e.to_string())),
+        };
+        let file = match file.into_inner() {
+            Ok(file) => file,
+            Err(e) => return Err(SitemapError::Write(e.error().to_string())),
+        };
+        if let Err(e) = file.sync_all() {
+            return Err(SitemapError::Write(e.to_string()));
+        }
+        drop(file);
+        self.temp.persist()
+    }
+}
+
+impl Write for SitemapFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.writer {
+            OutputWriter::Plain(file) => file.write(buf),
+            #[cfg(feature = "gzip")]
+            OutputWriter::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.writer {
+            OutputWriter::Plain(file) => file.flush(),
+            #[cfg(feature = "gzip")]
+            OutputWriter::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// The path of a temporary file next to a target, removed when dropped
+/// unless it was persisted.
+pub(crate) struct TempFile {
+    path: PathBuf,
+    target: PathBuf,
+    persisted: bool,
+}
+
+impl TempFile {
+    /// Picks a hidden, unique name in the directory of `target`, so that the
+    /// final rename stays on the same file system.
+    pub(crate) fn new(target: &Path) -> TempFile {
+        let name = target
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let temp_name = format!(
+            ".{}.{}-{}.tmp",
+            name,
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+        TempFile {
+            path: target.with_file_name(temp_name),
+            target: target.to_path_buf(),
+            persisted: false,
+        }
+    }
+
+    /// Returns the path of the temporary file.
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the path the temporary file will be renamed to.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Renames the temporary file over the target and syncs the directory.
+    pub(crate) fn persist(self) -> Result<(), SitemapError> {
+        if let Err(e) = fs::rename(&self.path, &self.target) {
+            return Err(SitemapError::Write(e.to_string()));
+        }
+        let target = self.target.clone();
+        self.keep();
+        match sync_dir(parent_dir(&target)) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(SitemapError::Write(e.to_string())),
+        }
+    }
+
+    /// Keeps the file after it was renamed by the caller.
+    pub(crate) fn keep(mut self) {
+        self.persisted = true;
+    }
+}
+
+/// Removes the file unless it was persisted or kept.
+///
+/// The removal is a blocking call. The async writers remove their temporary
+/// files themselves, so it only runs there if their future is dropped
+/// before it completes.
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Returns the directory holding `path`.
+pub(crate) fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    }
+}
+
+/// Syncs a directory, so that a rename in it reaches the disk.
+///
+/// Directories cannot be opened as files on Windows, where this does
+/// nothing.
+fn sync_dir(dir: &Path) -> std::io::Result<()> {
+    if cfg!(unix) {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+fn open(path: &Path) -> Result<(File, TempFile), SitemapError> {
+    let temp = TempFile::new(path);
+    match OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(temp.path())
+    {
+        Ok(file) => Ok((file, temp)),
+        Err(e) => {
+            // Nothing was created, so there is nothing to remove.
+            temp.keep();
+            Err(SitemapError::FileOpen(e.to_string()))
+        }
+    }
+}
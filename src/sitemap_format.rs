@@ -0,0 +1,124 @@
+use std::borrow::Cow;
+
+/// The XML declaration every document written by this crate starts with.
+pub(crate) const XML_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+/// Controls how the writers lay out the XML.
+///
+/// The default is the compact form: the whole document on a single line, as
+/// written by earlier versions. [`SitemapFormat::pretty`] puts every element
+/// on its own line and indents nested elements, which keeps diffs of
+/// committed sitemaps readable. Elements that only hold text, like
+/// `<loc>...</loc>`, always stay on one line.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapFormat, SitemapUrl, SitemapWriter};
+///
+/// let xml = SitemapWriter::build_with_format(
+///     vec![SitemapUrl::new("https://example.com/")],
+///     SitemapFormat::pretty(),
+/// );
+/// assert!(xml.contains("\n  <url>\n    <loc>https://example.com/</loc>\n  </url>\n"));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SitemapFormat {
+    /// Starts every element on its own line.
+    pub newlines: bool,
+
+    /// The number of spaces per nesting level. Only used together with
+    /// `newlines`.
+    pub indent: usize,
+}
+
+impl SitemapFormat {
+    /// Returns the compact, single-line format. This is the default.
+    pub fn compact() -> SitemapFormat {
+        SitemapFormat::default()
+    }
+
+    /// Returns a format with one element per line and two spaces of
+    /// indentation.
+    pub fn pretty() -> SitemapFormat {
+        SitemapFormat {
+            newlines: true,
+            indent: 2,
+        }
+    }
+
+    /// Lays out a single tag, such as the XML declaration or the root tags.
+    pub(crate) fn line<'a>(&self, tag: &'a str, depth: usize) -> Cow<'a, str> {
+        if !self.newlines {
+            return Cow::Borrowed(tag);
+        }
+        let mut out = String::new();
+        self.push_line(&mut out, depth, tag.trim());
+        Cow::Owned(out)
+    }
+
+    /// Lays out a rendered element and its children, starting at `depth`.
+    pub(crate) fn element<'a>(&self, xml: &'a str, depth: usize) -> Cow<'a, str> {
+        if !self.newlines {
+            return Cow::Borrowed(xml);
+        }
+        let tokens = tokenize(xml);
+        let mut out = String::with_capacity(xml.len() * 2);
+        let mut depth = depth;
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = tokens[i];
+            if token.starts_with("</") {
+                depth = depth.saturating_sub(1);
+                self.push_line(&mut out, depth, token);
+            } else if !token.starts_with('<') || token.ends_with("/>") {
+                self.push_line(&mut out, depth, token);
+            } else {
+                // Keep `<a>text</a>` and `<a></a>` on a single line.
+                match (tokens.get(i + 1), tokens.get(i + 2)) {
+                    (Some(close), _) if close.starts_with("</") => {
+                        self.push_line(&mut out, depth, &format!("{}{}", token, close));
+                        i += 1;
+                    }
+                    (Some(text), Some(close))
+                        if !text.starts_with('<') && close.starts_with("</") =>
+                    {
+                        let line = format!("{}{}{}", token, text, close);
+                        self.push_line(&mut out, depth, &line);
+                        i += 2;
+                    }
+                    _ => {
+                        self.push_line(&mut out, depth, token);
+                        depth += 1;
+                    }
+                }
+            }
+            i += 1;
+        }
+        Cow::Owned(out)
+    }
+
+    fn push_line(&self, out: &mut String, depth: usize, line: &str) {
+        out.extend(std::iter::repeat_n(' ', self.indent * depth));
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+/// Splits rendered XML into tags and text runs.
+///
+/// The input is produced by the renderers, so text never contains a raw `<`
+/// and attribute values never contain a raw `>`.
+fn tokenize(xml: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut rest = xml;
+    while !rest.is_empty() {
+        let end = match rest.strip_prefix('<') {
+            Some(tag) => tag.find('>').map_or(rest.len(), |i| i + 2),
+            None => rest.find('<').unwrap_or(rest.len()),
+        };
+        tokens.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    tokens
+}
@@ -0,0 +1,121 @@
+/// The maximum number of images a single `<url>` entry may contain.
+pub const MAX_IMAGES_PER_URL: usize = 1_000;
+
+/// Represents a single image attached to a [`crate::SitemapUrl`]
+/// (Google image sitemap extension).
+///
+/// Only `loc` is still used by Google; `caption`, `title` and `license` are
+/// legacy fields that are written when present.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapImage, SitemapUrl};
+///
+/// let mut url = SitemapUrl::new("https://example.com/gallery/");
+/// url.images.push(SitemapImage::new("https://example.com/images/1.jpg"));
+/// url.images.push(SitemapImage {
+///     loc: "https://example.com/images/2.jpg".to_string(),
+///     caption: Some("A sunset".to_string()),
+///     ..SitemapImage::default()
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SitemapImage {
+    /// The URL of the image. This is the only required field.
+    pub loc: String,
+
+    /// The caption of the image.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub caption: Option<String>,
+
+    /// The title of the image.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub title: Option<String>,
+
+    /// A URL to the license of the image.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub license: Option<String>,
+}
+
+impl SitemapImage {
+    /// Creates a new `SitemapImage` with only the image URL specified.
+    ///
+    /// # Arguments
+    ///
+    /// * `loc` - The URL of the image.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::SitemapImage;
+    ///
+    /// let image = SitemapImage::new("https://example.com/photo.jpg");
+    /// assert_eq!(image.loc, "https://example.com/photo.jpg");
+    /// assert!(image.caption.is_none());
+    /// ```
+    pub fn new(loc: &str) -> SitemapImage {
+        SitemapImage {
+            loc: loc.to_string(),
+            ..SitemapImage::default()
+        }
+    }
+}
+
+impl From<&str> for SitemapImage {
+    fn from(loc: &str) -> Self {
+        SitemapImage::new(loc)
+    }
+}
+
+impl From<String> for SitemapImage {
+    fn from(loc: String) -> Self {
+        SitemapImage {
+            loc,
+            ..SitemapImage::default()
+        }
+    }
+}
+
+/// Renders a single `<image:image>` element.
+pub(crate) fn render_image(image: &SitemapImage) -> String {
+    let mut row = "<image:image>".to_string();
+    row += format!(
+        "<image:loc>{}</image:loc>",
+        html_escape::encode_text(image.loc.as_str())
+    )
+    .as_str();
+    if let Some(caption) = &image.caption {
+        row += format!(
+            "<image:caption>{}</image:caption>",
+            html_escape::encode_text(caption)
+        )
+        .as_str();
+    }
+    if let Some(title) = &image.title {
+        row += format!(
+            "<image:title>{}</image:title>",
+            html_escape::encode_text(title)
+        )
+        .as_str();
+    }
+    if let Some(license) = &image.license {
+        row += format!(
+            "<image:license>{}</image:license>",
+            html_escape::encode_text(license)
+        )
+        .as_str();
+    }
+    row += "</image:image>";
+    row
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::error::SitemapError;
+use crate::sitemap_datetime::SitemapLastmod;
+use crate::sitemap_file::SitemapFile;
+use crate::sitemap_reader::{SitemapEntry, SitemapReader};
+use crate::sitemap_url::SitemapUrl;
+use crate::sitemap_writer::SitemapWriter;
+
+/// The first line of a manifest file.
+const MANIFEST_HEADER: &str = "# sitemap-writer manifest v1";
+
+/// Incremental sitemap regeneration that keeps the real `lastmod` of pages
+/// whose content did not change.
+///
+/// [`SitemapIncremental::open`] reads the previously published sitemap (or
+/// sitemap index and its shards) and a sidecar manifest holding a content
+/// hash per URL. Each URL of the new build is then passed to
+/// [`SitemapIncremental::update`] together with the hash of its content:
+///
+/// * if the hash matches the manifest, the `lastmod` of the previous sitemap
+///   is kept,
+/// * if the manifest has no hash for the page but the previous sitemap has a
+///   `lastmod` for it, e.g. on the first run with an existing sitemap, that
+///   `lastmod` is kept,
+/// * if the page is new or its hash changed, `lastmod` is set to the time of
+///   the build.
+///
+/// [`SitemapIncremental::save_manifest`] then records the new hashes. The
+/// manifest is a text file with a `#` header line followed by one
+/// `<hash>\t<loc>` line per URL, stored at `<sitemap path>.manifest` unless
+/// configured otherwise. Backslashes, tabs and line breaks in hashes and URLs
+/// are escaped as `\\`, `\t`, `\n` and `\r`, so any string works as a hash,
+/// e.g. a hex-encoded SHA-256 digest.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sitemap_writer::{SitemapIncremental, SitemapUrl};
+///
+/// let mut incremental = SitemapIncremental::open("public/sitemap.xml").unwrap();
+/// incremental
+///     .make(vec![
+///         (SitemapUrl::new("https://example.com/"), "9f86d081".to_string()),
+///         (SitemapUrl::new("https://example.com/about/"), "60303ae2".to_string()),
+///     ])
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SitemapIncremental {
+    sitemap_path: String,
+    manifest_path: String,
+    now: String,
+    previous_lastmods: HashMap<String, String>,
+    previous_hashes: HashMap<String, String>,
+    hashes: Vec<(String, String)>,
+}
+
+impl SitemapIncremental {
+    /// Reads the previously published sitemap set and its manifest.
+    ///
+    /// Missing files are treated as a first run: every URL counts as
+    /// changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `sitemap_path` - The path of the published sitemap or sitemap
+    ///   index. The shards of an index are looked up in the same directory,
+    ///   by the last path segment of their `loc`.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if an existing sitemap, shard or manifest cannot be
+    /// read or parsed.
+    pub fn open(sitemap_path: &str) -> Result<SitemapIncremental, SitemapError> {
+        let manifest_path = format!("{}.manifest", sitemap_path);
+        SitemapIncremental::with_manifest(sitemap_path, &manifest_path)
+    }
+
+    /// Reads the previously published sitemap set and the manifest at the
+    /// given path.
+    pub fn with_manifest(
+        sitemap_path: &str,
+        manifest_path: &str,
+    ) -> Result<SitemapIncremental, SitemapError> {
+        let mut previous_lastmods = HashMap::new();
+        if Path::new(sitemap_path).exists() {
+            for url in read_sitemap_set(sitemap_path)? {
+                if let Some(lastmod) = url.lastmod {
+                    previous_lastmods.insert(url.loc, lastmod);
+                }
+            }
+        }
+        Ok(SitemapIncremental {
+            sitemap_path: sitemap_path.to_string(),
+            manifest_path: manifest_path.to_string(),
+            now: SitemapLastmod::from(SystemTime::now()).to_string(),
+            previous_lastmods,
+            previous_hashes: read_manifest(manifest_path)?,
+            hashes: vec![],
+        })
+    }
+
+    /// Sets the `lastmod` given to new and changed pages. Defaults to the
+    /// time [`SitemapIncremental::open`] was called.
+    pub fn with_now(mut self, now: impl Into<SitemapLastmod>) -> SitemapIncremental {
+        self.now = now.into().to_string();
+        self
+    }
+
+    /// Returns `true` if the page is new or its hash differs from the
+    /// manifest.
+    pub fn is_changed(&self, loc: &str, hash: &str) -> bool {
+        self.previous_hashes.get(loc).map(String::as_str) != Some(hash)
+    }
+
+    /// Sets the `lastmod` of a URL of the new build and records its hash.
+    ///
+    /// An unchanged page gets the `lastmod` it had in the previous sitemap;
+    /// if it had none, its own `lastmod` is kept. A page without a manifest
+    /// entry keeps the `lastmod` of the previous sitemap if there is one,
+    /// since there is no hash to tell whether it changed. A new or changed
+    /// page gets the time of the build.
+    pub fn update(&mut self, mut url: SitemapUrl, hash: &str) -> SitemapUrl {
+        let previous_lastmod = self.previous_lastmods.get(&url.loc);
+        let unhashed = !self.previous_hashes.contains_key(&url.loc);
+        if !self.is_changed(&url.loc, hash) || (unhashed && previous_lastmod.is_some()) {
+            if let Some(lastmod) = previous_lastmod {
+                url.lastmod = Some(lastmod.clone());
+            }
+        } else {
+            url.lastmod = Some(self.now.clone());
+        }
+        self.hashes.push((url.loc.clone(), hash.to_string()));
+        url
+    }
+
+    /// Applies [`SitemapIncremental::update`] to each URL and hash pair.
+    pub fn update_all(&mut self, entries: Vec<(SitemapUrl, String)>) -> Vec<SitemapUrl> {
+        entries
+            .into_iter()
+            .map(|(url, hash)| self.update(url, &hash))
+            .collect()
+    }
+
+    /// Writes the hashes recorded by [`SitemapIncremental::update`] to the
+    /// manifest, replacing the previous one. Pages that were not updated are
+    /// dropped from the manifest.
+    pub fn save_manifest(&self) -> Result<(), SitemapError> {
+        let mut content = format!("{}\n", MANIFEST_HEADER);
+        for (loc, hash) in &self.hashes {
+            content += format!("{}\t{}\n", escape(hash), escape(loc)).as_str();
+        }
+        let mut file = SitemapFile::create(&self.manifest_path)?;
+        if let Err(e) = file.write_all(content.as_bytes()) {
+            return Err(SitemapError::Write(e.to_string()));
+        }
+        file.close()
+    }
+
+    /// Updates the URLs, writes them to the sitemap path given to
+    /// [`SitemapIncremental::open`] and saves the manifest.
+    ///
+    /// For a sharded set, call [`SitemapIncremental::update_all`], write the
+    /// URLs with a [`crate::ShardedSitemapWriter`] and then
+    /// [`SitemapIncremental::save_manifest`].
+    pub fn make(&mut self, entries: Vec<(SitemapUrl, String)>) -> Result<(), SitemapError> {
+        let urls = self.update_all(entries);
+        SitemapWriter::make(&self.sitemap_path, urls)?;
+        self.save_manifest()
+    }
+}
+
+/// Reads the URLs of a sitemap, or of all shards of a sitemap index.
+fn read_sitemap_set(path: &str) -> Result<Vec<SitemapUrl>, SitemapError> {
+    let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+    let mut urls = vec![];
+    for entry in SitemapReader::open(path)? {
+        match entry? {
+            SitemapEntry::Url(url) => urls.push(url),
+            SitemapEntry::Sitemap(sitemap) => {
+                let loc = sitemap.loc.split(['?', '#']).next().unwrap_or_default();
+                let name = loc.rsplit('/').next().unwrap_or_default();
+                let shard = dir.join(name);
+                urls.extend(SitemapReader::open(&shard.to_string_lossy())?.read_urls()?);
+            }
+        }
+    }
+    Ok(urls)
+}
+
+fn read_manifest(path: &str) -> Result<HashMap<String, String>, SitemapError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(SitemapError::Read(e.to_string())),
+    };
+    let mut hashes = HashMap::new();
+    for (index, line) in content.lines().enumerate() {
+        // Only the header line is a comment: an escaped hash may start
+        // with `#`.
+        if line.is_empty() || (index == 0 && line.starts_with('#')) {
+            continue;
+        }
+        let error = |message: &str| SitemapError::Parse {
+            line: index + 1,
+            column: 1,
+            message: message.to_string(),
+        };
+        let (hash, loc) = line
+            .split_once('\t')
+            .ok_or_else(|| error("expected <hash>\\t<loc>"))?;
+        let hash = unescape(hash).ok_or_else(|| error("invalid escape in hash"))?;
+        let loc = unescape(loc).ok_or_else(|| error("invalid escape in loc"))?;
+        hashes.insert(loc, hash);
+    }
+    Ok(hashes)
+}
+
+/// Escapes the characters that would break a manifest line.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverses [`escape`], or returns `None` for an unknown escape sequence.
+fn unescape(value: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        unescaped.push(match chars.next()? {
+            '\\' => '\\',
+            't' => '\t',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(unescaped)
+}
@@ -1,7 +1,24 @@
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
+use std::path::Path;
+
+#[cfg(feature = "gzip")]
+use flate2::{Compression, write::GzEncoder};
 
 use crate::error::SitemapError;
+use crate::sitemap_datetime::{SitemapLastmod, parse_timestamp};
+use crate::sitemap_file::SitemapFile;
+use crate::sitemap_format::{SitemapFormat, XML_HEADER};
+use crate::sitemap_reader::{SitemapEntry, SitemapReader};
+use crate::sitemap_scanner::encode_path;
+use crate::sitemap_sharded::join_url;
+use crate::validation::{
+    SitemapDiagnostic, SitemapIssue, diagnose, validate_lastmod, validate_loc,
+};
+
+pub(crate) const SITEMAPINDEX_OPEN: &str =
+    r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#;
+pub(crate) const SITEMAPINDEX_CLOSE: &str = r#"</sitemapindex>"#;
 
 /// Represents a single sitemap entry in a sitemap index.
 ///
@@ -29,6 +46,7 @@ use crate::error::SitemapError;
 /// let sitemap = SitemapIndex::new("https://example.com/sitemap1.xml");
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SitemapIndex {
     /// The URL of the sitemap file. This is a required field.
     ///
@@ -38,6 +56,11 @@ pub struct SitemapIndex {
     /// The date of last modification of the sitemap file.
     ///
     /// Should be in W3C Datetime format (e.g., `2024-01-15` or `2024-01-15T12:00:00+00:00`).
+    /// Use [`SitemapIndex::set_lastmod`] to set it from a typed date.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub lastmod: Option<String>,
 }
 
@@ -50,6 +73,17 @@ impl Default for SitemapIndex {
     }
 }
 
+/// The sitemap files found by [`SitemapIndexWriter::scan_dir`].
+#[derive(Debug, Default)]
+pub struct SitemapDirScan {
+    /// The index entries of the readable sitemap files, sorted by file name.
+    pub sitemaps: Vec<SitemapIndex>,
+
+    /// The file names of the sitemap files that could not be read, with the
+    /// error for each, sorted by file name.
+    pub errors: Vec<(String, SitemapError)>,
+}
+
 impl SitemapIndex {
     /// Creates a new `SitemapIndex` with only the URL specified.
     ///
@@ -74,6 +108,36 @@ impl SitemapIndex {
             ..SitemapIndex::default()
         }
     }
+
+    /// Sets `lastmod` from a typed date, formatted as a W3C Datetime.
+    ///
+    /// See [`crate::SitemapUrl::set_lastmod`].
+    pub fn set_lastmod(&mut self, lastmod: impl Into<SitemapLastmod>) {
+        self.lastmod = Some(lastmod.into().to_string());
+    }
+
+    /// Returns `lastmod` parsed as a W3C Datetime.
+    ///
+    /// See [`crate::SitemapUrl::parsed_lastmod`].
+    pub fn parsed_lastmod(&self) -> Option<SitemapLastmod> {
+        self.lastmod.as_deref().map(SitemapLastmod::from)
+    }
+
+    /// Checks the entry against the sitemap protocol.
+    ///
+    /// `loc` must be an absolute `http`/`https` URL of at most 2,048
+    /// characters and `lastmod` must be a W3C Datetime.
+    ///
+    /// # Returns
+    ///
+    /// Returns every problem found, or an empty `Vec` if the entry is valid.
+    pub fn validate(&self) -> Vec<SitemapIssue> {
+        let mut issues = validate_loc(&self.loc);
+        if let Some(lastmod) = &self.lastmod {
+            issues.extend(validate_lastmod(lastmod));
+        }
+        issues
+    }
 }
 
 /// A writer for generating XML sitemap index files.
@@ -81,8 +145,22 @@ impl SitemapIndex {
 /// Use this when your site has more than 50,000 URLs or when you want to
 /// organize sitemaps by category (e.g., products, blog posts, pages).
 ///
+/// Like [`crate::SitemapWriter`], entries can be streamed one at a time to any
+/// [`std::io::Write`] sink.
+///
 /// # Examples
 ///
+/// ## Streaming to any writer
+///
+/// ```rust
+/// use sitemap_writer::{SitemapIndexWriter, SitemapIndex};
+///
+/// let mut writer = SitemapIndexWriter::new(Vec::new()).unwrap();
+/// writer.push(&SitemapIndex::new("https://example.com/sitemap1.xml")).unwrap();
+/// let xml = String::from_utf8(writer.finish().unwrap()).unwrap();
+/// assert!(xml.contains("<loc>https://example.com/sitemap1.xml</loc>"));
+/// ```
+///
 /// ## Writing to a file
 ///
 /// ```rust,no_run
@@ -108,11 +186,111 @@ impl SitemapIndex {
 /// ]);
 /// assert!(xml.contains("<sitemapindex"));
 /// ```
-pub struct SitemapIndexWriter {}
+pub struct SitemapIndexWriter<W: Write = File> {
+    writer: W,
+    format: SitemapFormat,
+    strict: bool,
+    sitemap_count: usize,
+    bytes_written: usize,
+}
+
+impl<W: Write> SitemapIndexWriter<W> {
+    /// Starts a new sitemap index on the given writer.
+    ///
+    /// The XML declaration and the opening `<sitemapindex>` tag are written
+    /// immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Any [`std::io::Write`] sink.
+    pub fn new(writer: W) -> Result<SitemapIndexWriter<W>, SitemapError> {
+        SitemapIndexWriter::with_format(writer, SitemapFormat::default())
+    }
+
+    /// Starts a new sitemap index on the given writer, laying out the XML
+    /// with the given format.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Any [`std::io::Write`] sink.
+    /// * `format` - The layout of the XML, see [`SitemapFormat`].
+    pub fn with_format(
+        writer: W,
+        format: SitemapFormat,
+    ) -> Result<SitemapIndexWriter<W>, SitemapError> {
+        let mut index = SitemapIndexWriter {
+            writer,
+            format,
+            strict: false,
+            sitemap_count: 0,
+            bytes_written: 0,
+        };
+        index.write_text(&format.line(XML_HEADER, 0))?;
+        index.write_text(&format.line(SITEMAPINDEX_OPEN, 0))?;
+        Ok(index)
+    }
+
+    /// Writes a single `<sitemap>` entry.
+    ///
+    /// In strict mode, returns a [`SitemapError::InvalidEntry`] without
+    /// writing anything if the entry is invalid.
+    pub fn push(&mut self, sitemap: &SitemapIndex) -> Result<(), SitemapError> {
+        if self.strict {
+            check_valid_sitemap(sitemap, self.sitemap_count)?;
+        }
+        let row = render_sitemap(sitemap);
+        let row = self.format.element(&row, 1);
+        self.write_text(&row)?;
+        self.sitemap_count += 1;
+        Ok(())
+    }
+
+    /// Enables or disables strict mode.
+    ///
+    /// In strict mode, [`SitemapIndexWriter::push`] refuses entries for which
+    /// [`SitemapIndex::validate`] reports problems.
+    pub fn strict(mut self, strict: bool) -> SitemapIndexWriter<W> {
+        self.strict = strict;
+        self
+    }
+
+    /// Writes the closing `</sitemapindex>` tag, flushes and returns the
+    /// underlying writer.
+    pub fn finish(mut self) -> Result<W, SitemapError> {
+        let close = self.format.line(SITEMAPINDEX_CLOSE, 0);
+        self.write_text(&close)?;
+        match self.writer.flush() {
+            Ok(_) => Ok(self.writer),
+            Err(e) => Err(SitemapError::Write(e.to_string())),
+        }
+    }
+
+    /// Returns the number of `<sitemap>` entries written so far.
+    pub fn sitemap_count(&self) -> usize {
+        self.sitemap_count
+    }
+
+    /// Returns the number of bytes written so far, including the header.
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    fn write_text(&mut self, str: &str) -> Result<(), SitemapError> {
+        if let Err(e) = self.writer.write_all(str.as_bytes()) {
+            return Err(SitemapError::Write(e.to_string()));
+        }
+        self.bytes_written += str.len();
+        Ok(())
+    }
+}
 
 impl SitemapIndexWriter {
     /// Creates a sitemap index XML file at the specified path.
     ///
+    /// The file is written to a temporary file in the same directory and
+    /// renamed over `path` once complete, so an existing index is only
+    /// replaced if the whole write succeeded.
+    ///
     /// # Arguments
     ///
     /// * `path` - The file path where the sitemap index will be written.
@@ -138,35 +316,55 @@ impl SitemapIndexWriter {
     /// assert!(result.is_ok());
     /// ```
     pub fn make(path: &str, sitemaps: Vec<SitemapIndex>) -> Result<(), SitemapError> {
-        let file = File::create(path);
-        if file.is_err() {
-            return Err(SitemapError::FileOpen(file.err().unwrap().to_string()));
-        }
-        let mut file = file.unwrap();
-        write_text(&file, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
-        write_text(
-            &file,
-            r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#,
-        )?;
-
-        for sitemap in sitemaps {
-            let mut row = "<sitemap>".to_string();
-            row += format!(
-                "<loc>{}</loc>",
-                html_escape::encode_text(sitemap.loc.as_str())
-            )
-            .as_str();
-            if let Some(lastmod) = sitemap.lastmod {
-                row += format!("<lastmod>{}</lastmod>", lastmod).as_str();
-            }
-            row += "</sitemap>";
-            write_text(&file, row.as_str())?;
-        }
-        write_text(&file, r#"</sitemapindex>"#)?;
-        match file.flush() {
-            Ok(_) => Ok(()),
-            Err(e) => Err(SitemapError::Write(e.to_string())),
-        }
+        SitemapIndexWriter::make_with_format(path, sitemaps, SitemapFormat::default())
+    }
+
+    /// Creates a sitemap index XML file at the specified path, laid out with
+    /// the given format.
+    pub fn make_with_format(
+        path: &str,
+        sitemaps: Vec<SitemapIndex>,
+        format: SitemapFormat,
+    ) -> Result<(), SitemapError> {
+        write_file(SitemapFile::create(path)?, &sitemaps, format)
+    }
+
+    /// Creates a gzip-compressed sitemap index file at the specified path.
+    ///
+    /// Requires the `gzip` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::{SitemapIndexWriter, SitemapIndex};
+    ///
+    /// let result = SitemapIndexWriter::make_gz("sitemap_index.xml.gz", vec![
+    ///     SitemapIndex::new("https://example.com/sitemap1.xml.gz"),
+    /// ]);
+    /// assert!(result.is_ok());
+    /// ```
+    #[cfg(feature = "gzip")]
+    pub fn make_gz(path: &str, sitemaps: Vec<SitemapIndex>) -> Result<(), SitemapError> {
+        SitemapIndexWriter::make_gz_with_format(path, sitemaps, SitemapFormat::default())
+    }
+
+    /// Creates a gzip-compressed sitemap index file at the specified path,
+    /// laid out with the given format.
+    ///
+    /// Requires the `gzip` feature.
+    #[cfg(feature = "gzip")]
+    pub fn make_gz_with_format(
+        path: &str,
+        sitemaps: Vec<SitemapIndex>,
+        format: SitemapFormat,
+    ) -> Result<(), SitemapError> {
+        write_file(SitemapFile::create_gz(path)?, &sitemaps, format)
+    }
+
+    /// Validates every sitemap entry and returns a diagnostic for each invalid
+    /// one.
+    pub fn validate(sitemaps: &[SitemapIndex]) -> Vec<SitemapDiagnostic> {
+        diagnose(sitemaps, |sitemap| &sitemap.loc, SitemapIndex::validate)
     }
 
     /// Builds a sitemap index XML string from the provided sitemaps.
@@ -200,33 +398,251 @@ impl SitemapIndexWriter {
     /// // HttpResponse::Ok().content_type("application/xml").body(xml)
     /// ```
     pub fn build(sitemaps: Vec<SitemapIndex>) -> String {
+        SitemapIndexWriter::build_with_format(sitemaps, SitemapFormat::default())
+    }
+
+    /// Builds a sitemap index XML string from the provided sitemaps, laid out
+    /// with the given format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapFormat, SitemapIndex, SitemapIndexWriter};
+    ///
+    /// let xml = SitemapIndexWriter::build_with_format(
+    ///     vec![SitemapIndex::new("https://example.com/sitemap1.xml")],
+    ///     SitemapFormat {
+    ///         newlines: true,
+    ///         indent: 4,
+    ///     },
+    /// );
+    /// assert!(xml.contains("\n    <sitemap>\n        <loc>"));
+    /// ```
+    pub fn build_with_format(sitemaps: Vec<SitemapIndex>, format: SitemapFormat) -> String {
         let mut content = String::new();
-        content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
-        content.push_str(r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
-
-        for sitemap in sitemaps {
-            let mut row = "<sitemap>".to_string();
-            row += format!(
-                "<loc>{}</loc>",
-                html_escape::encode_text(sitemap.loc.as_str())
-            )
-            .as_str();
-            if let Some(lastmod) = sitemap.lastmod {
-                row += format!("<lastmod>{}</lastmod>", lastmod).as_str();
+        content.push_str(&format.line(XML_HEADER, 0));
+        content.push_str(&format.line(SITEMAPINDEX_OPEN, 0));
+        for sitemap in &sitemaps {
+            content.push_str(&format.element(&render_sitemap(sitemap), 1));
+        }
+        content.push_str(&format.line(SITEMAPINDEX_CLOSE, 0));
+        content
+    }
+
+    /// Builds the index entries for the sitemap files in a directory.
+    ///
+    /// Every `*.xml` and `*.xml.gz` file directly in `dir` becomes an entry
+    /// whose `loc` is the file name appended to `base_url`. Its `lastmod` is
+    /// the newest `<lastmod>` of the URLs in the file, or the file
+    /// modification time if no URL has a valid one. Hidden files, sitemap
+    /// indexes and XML files whose root element is not `<urlset>` (such as
+    /// an RSS or Atom feed) are skipped, so the index itself may live in the
+    /// same directory. Entries are sorted by file name.
+    ///
+    /// `.xml.gz` files are decompressed and read with the `gzip` feature
+    /// enabled. Without it, they cannot be inspected and are reported as
+    /// errors instead of being listed.
+    ///
+    /// A file that cannot be read or is malformed does not stop the scan: it
+    /// is left out of the entries and reported in
+    /// [`SitemapDirScan::errors`].
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory holding the sitemap files.
+    /// * `base_url` - The public URL of `dir` (e.g.
+    ///   `https://example.com/sitemaps/`).
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`SitemapError::Read`] if the directory itself cannot be
+    /// read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::SitemapIndexWriter;
+    ///
+    /// let scan =
+    ///     SitemapIndexWriter::scan_dir("public/sitemaps", "https://example.com/sitemaps/").unwrap();
+    /// for sitemap in &scan.sitemaps {
+    ///     println!("{}", sitemap.loc);
+    /// }
+    /// for (name, e) in &scan.errors {
+    ///     eprintln!("skipped {}: {}", name, e);
+    /// }
+    /// ```
+    pub fn scan_dir(dir: &str, base_url: &str) -> Result<SitemapDirScan, SitemapError> {
+        let read_error = |e: std::io::Error| SitemapError::Read(format!("{}: {}", dir, e));
+        let mut entries = fs::read_dir(dir)
+            .map_err(read_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(read_error)?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        let mut scan = SitemapDirScan::default();
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let gz = name.ends_with(".xml.gz");
+            if name.starts_with('.') || !(gz || name.ends_with(".xml")) {
+                continue;
+            }
+            match scan_file(&entry.path(), gz) {
+                Ok(Some(lastmod)) => scan.sitemaps.push(SitemapIndex {
+                    loc: join_url(base_url, &encode_path(&name)),
+                    lastmod: Some(lastmod),
+                }),
+                Ok(None) => {}
+                Err(e) => scan.errors.push((name, e)),
             }
-            row += "</sitemap>";
-            content.push_str(&row);
         }
+        Ok(scan)
+    }
 
-        content.push_str(r#"</sitemapindex>"#);
-        content
+    /// Scans a directory for sitemap files with
+    /// [`SitemapIndexWriter::scan_dir`] and writes a sitemap index for them.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file path where the sitemap index will be written.
+    /// * `dir` - The directory holding the sitemap files.
+    /// * `base_url` - The public URL of `dir`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the entries written to the index and the files left out
+    /// because they could not be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::SitemapIndexWriter;
+    ///
+    /// let scan = SitemapIndexWriter::make_from_dir(
+    ///     "public/sitemap_index.xml",
+    ///     "public/sitemaps",
+    ///     "https://example.com/sitemaps/",
+    /// )
+    /// .unwrap();
+    /// assert!(scan.errors.is_empty());
+    /// ```
+    pub fn make_from_dir(
+        path: &str,
+        dir: &str,
+        base_url: &str,
+    ) -> Result<SitemapDirScan, SitemapError> {
+        let scan = SitemapIndexWriter::scan_dir(dir, base_url)?;
+        SitemapIndexWriter::make(path, scan.sitemaps.clone())?;
+        Ok(scan)
+    }
+}
+
+/// Reads a file for [`SitemapIndexWriter::scan_dir`], returning its
+/// `lastmod`, or `None` if it is not a `<urlset>` file.
+fn scan_file(path: &Path, gz: bool) -> Result<Option<String>, SitemapError> {
+    let read_error = |e: std::io::Error| SitemapError::Read(format!("{}: {}", path.display(), e));
+    let metadata = fs::metadata(path).map_err(read_error)?;
+    if !metadata.is_file() {
+        return Ok(None);
+    }
+    if gz && !cfg!(feature = "gzip") {
+        return Err(SitemapError::Read(format!(
+            "{}: reading .xml.gz files requires the gzip feature",
+            path.display()
+        )));
+    }
+    let mut reader = SitemapReader::open(&path.to_string_lossy())?;
+    if reader.read_sitemap_root()?.as_deref() != Some("urlset") {
+        return Ok(None);
+    }
+    let mut lastmod = None;
+    while let Some(entry) = reader.next_entry()? {
+        if let SitemapEntry::Url(url) = entry {
+            lastmod = newest(lastmod, url.lastmod);
+        }
+    }
+    match lastmod {
+        Some(lastmod) => Ok(Some(lastmod)),
+        None => Ok(Some(
+            SitemapLastmod::from(metadata.modified().map_err(read_error)?).to_string(),
+        )),
+    }
+}
+
+/// Returns the newer of two `lastmod` values. Values that are not valid
+/// W3C Datetimes are ignored.
+fn newest(current: Option<String>, candidate: Option<String>) -> Option<String> {
+    match candidate {
+        Some(candidate)
+            if parse_timestamp(&candidate) > current.as_deref().and_then(parse_timestamp) =>
+        {
+            Some(candidate)
+        }
+        _ => current,
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl<W: Write> SitemapIndexWriter<GzEncoder<W>> {
+    /// Starts a new gzip-compressed sitemap index on the given writer.
+    ///
+    /// Use [`SitemapIndexWriter::finish_gz`] to close the index and write the
+    /// gzip trailer.
+    ///
+    /// Requires the `gzip` feature.
+    pub fn new_gz(writer: W) -> Result<SitemapIndexWriter<GzEncoder<W>>, SitemapError> {
+        SitemapIndexWriter::new(GzEncoder::new(writer, Compression::default()))
+    }
+
+    /// Writes the closing `</sitemapindex>` tag and the gzip trailer, and
+    /// returns the underlying writer.
+    pub fn finish_gz(self) -> Result<W, SitemapError> {
+        match self.finish()?.finish() {
+            Ok(writer) => Ok(writer),
+            Err(e) => Err(SitemapError::Write(e.to_string())),
+        }
+    }
+}
+
+fn write_file(
+    file: SitemapFile,
+    sitemaps: &[SitemapIndex],
+    format: SitemapFormat,
+) -> Result<(), SitemapError> {
+    let mut writer = SitemapIndexWriter::with_format(file, format)?;
+    for sitemap in sitemaps {
+        writer.push(sitemap)?;
+    }
+    writer.finish()?.close()
+}
+
+/// Refuses a sitemap entry with validation issues.
+pub(crate) fn check_valid_sitemap(
+    sitemap: &SitemapIndex,
+    index: usize,
+) -> Result<(), SitemapError> {
+    let issues = sitemap.validate();
+    if issues.is_empty() {
+        return Ok(());
     }
+    Err(SitemapError::InvalidEntry(SitemapDiagnostic {
+        index,
+        loc: sitemap.loc.clone(),
+        issues,
+    }))
 }
 
-fn write_text(mut file: &File, str: &str) -> Result<(), SitemapError> {
-    let f = file.write(str.as_bytes());
-    if f.is_err() {
-        return Err(SitemapError::Write(f.err().unwrap().to_string()));
+/// Renders a single `<sitemap>` element.
+pub(crate) fn render_sitemap(sitemap: &SitemapIndex) -> String {
+    let mut row = "<sitemap>".to_string();
+    row += format!(
+        "<loc>{}</loc>",
+        html_escape::encode_text(sitemap.loc.as_str())
+    )
+    .as_str();
+    if let Some(lastmod) = &sitemap.lastmod {
+        row += format!("<lastmod>{}</lastmod>", html_escape::encode_text(lastmod)).as_str();
     }
-    Ok(())
+    row += "</sitemap>";
+    row
 }
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use crate::error::SitemapError;
+use crate::sitemap_datetime::parse_timestamp;
+use crate::sitemap_index::SitemapIndex;
+use crate::sitemap_reader::SitemapReader;
+use crate::sitemap_sharded::ShardedSitemapWriter;
+use crate::sitemap_url::SitemapUrl;
+use crate::sitemap_writer::{SitemapNamespaces, SitemapWriter};
+
+/// The priority search engines assume when a URL has none.
+const DEFAULT_PRIORITY: f32 = 0.5;
+
+/// Decides which entry wins when the same `loc` is merged more than once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SitemapMergeStrategy {
+    /// The entry with the newest `lastmod` wins. Entries without a valid
+    /// `lastmod` are the oldest.
+    #[default]
+    NEWEST,
+    /// The entry with the highest `priority` wins. Entries without a
+    /// priority count as 0.5, the protocol default.
+    PRIORITY,
+    /// The first entry wins.
+    FIRST,
+}
+
+/// Merges URLs from several sources into one deduplicated set.
+///
+/// URLs are matched by `loc`. When a `loc` is pushed again, the
+/// [`SitemapMergeStrategy`] decides which entry is kept; on a tie the earlier
+/// entry stays. The merged set keeps the order in which each `loc` was first
+/// seen.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapMergeStrategy, SitemapMerger, SitemapUrl};
+///
+/// let mut blog = SitemapUrl::new("https://example.com/blog/");
+/// blog.lastmod = Some("2024-01-01".to_string());
+/// let mut shop = SitemapUrl::new("https://example.com/blog/");
+/// shop.lastmod = Some("2024-03-01".to_string());
+///
+/// let mut merger = SitemapMerger::new(SitemapMergeStrategy::NEWEST);
+/// merger.push_all(vec![blog, SitemapUrl::new("https://example.com/")]);
+/// merger.push_all(vec![shop]);
+///
+/// let urls = merger.into_urls();
+/// assert_eq!(urls.len(), 2);
+/// assert_eq!(urls[0].lastmod.as_deref(), Some("2024-03-01"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SitemapMerger {
+    strategy: SitemapMergeStrategy,
+    urls: Vec<SitemapUrl>,
+    positions: HashMap<String, usize>,
+}
+
+impl SitemapMerger {
+    /// Creates an empty merger using the given conflict resolution.
+    pub fn new(strategy: SitemapMergeStrategy) -> SitemapMerger {
+        SitemapMerger {
+            strategy,
+            urls: vec![],
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Adds a URL, resolving a conflict with an earlier entry for the same
+    /// `loc`.
+    pub fn push(&mut self, url: SitemapUrl) {
+        match self.positions.get(&url.loc) {
+            Some(&position) => {
+                if self.wins(&url, &self.urls[position]) {
+                    self.urls[position] = url;
+                }
+            }
+            None => {
+                self.positions.insert(url.loc.clone(), self.urls.len());
+                self.urls.push(url);
+            }
+        }
+    }
+
+    /// Adds all URLs of a source.
+    pub fn push_all<I: IntoIterator<Item = SitemapUrl>>(&mut self, urls: I) {
+        for url in urls {
+            self.push(url);
+        }
+    }
+
+    /// Adds all URLs of a sitemap file.
+    ///
+    /// With the `gzip` feature enabled, files ending in `.gz` are
+    /// decompressed transparently.
+    pub fn push_file(&mut self, path: &str) -> Result<(), SitemapError> {
+        self.push_all(SitemapReader::open(path)?.read_urls()?);
+        Ok(())
+    }
+
+    /// Returns the number of distinct URLs merged so far.
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    /// Returns `true` if no URL was merged yet.
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    /// Returns the merged URLs.
+    pub fn urls(&self) -> &[SitemapUrl] {
+        &self.urls
+    }
+
+    /// Returns the merged URLs, consuming the merger.
+    pub fn into_urls(self) -> Vec<SitemapUrl> {
+        self.urls
+    }
+
+    /// Writes the merged URLs to a sitemap file at the specified path.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::{SitemapMergeStrategy, SitemapMerger};
+    ///
+    /// let mut merger = SitemapMerger::new(SitemapMergeStrategy::FIRST);
+    /// merger.push_file("blog/sitemap.xml").unwrap();
+    /// merger.push_file("shop/sitemap.xml").unwrap();
+    /// merger.make("public/sitemap.xml").unwrap();
+    /// ```
+    pub fn make(self, path: &str) -> Result<(), SitemapError> {
+        SitemapWriter::make(path, self.urls)
+    }
+
+    /// Writes the merged URLs through a sharded writer and finishes it with
+    /// a sitemap index.
+    ///
+    /// The namespaces required by the merged URLs are declared on the
+    /// writer, replacing any set with
+    /// [`ShardedSitemapWriter::with_namespaces`].
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - A configured [`ShardedSitemapWriter`] with no URLs yet.
+    /// * `index_name` - The file name of the index, passed to
+    ///   [`ShardedSitemapWriter::finish`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::{ShardedSitemapWriter, SitemapMergeStrategy, SitemapMerger};
+    ///
+    /// let mut merger = SitemapMerger::new(SitemapMergeStrategy::NEWEST);
+    /// merger.push_file("blog/sitemap.xml").unwrap();
+    /// merger.push_file("shop/sitemap.xml").unwrap();
+    ///
+    /// let writer = ShardedSitemapWriter::new(
+    ///     "public/sitemaps",
+    ///     "sitemap-{}.xml",
+    ///     "https://example.com/sitemaps/",
+    /// ).unwrap();
+    /// let sitemaps = merger.make_sharded(writer, "sitemap_index.xml").unwrap();
+    /// ```
+    pub fn make_sharded(
+        self,
+        writer: ShardedSitemapWriter,
+        index_name: &str,
+    ) -> Result<Vec<SitemapIndex>, SitemapError> {
+        let mut writer = writer.with_namespaces(SitemapNamespaces::from_urls(&self.urls));
+        for url in &self.urls {
+            writer.push(url)?;
+        }
+        writer.finish(index_name)
+    }
+
+    /// Builds the sitemap XML string from the merged URLs.
+    pub fn build(self) -> String {
+        SitemapWriter::build(self.urls)
+    }
+
+    /// Returns `true` if `url` should replace `current`.
+    fn wins(&self, url: &SitemapUrl, current: &SitemapUrl) -> bool {
+        match self.strategy {
+            SitemapMergeStrategy::NEWEST => {
+                let timestamp = |url: &SitemapUrl| url.lastmod.as_deref().and_then(parse_timestamp);
+                timestamp(url) > timestamp(current)
+            }
+            SitemapMergeStrategy::PRIORITY => {
+                url.priority.unwrap_or(DEFAULT_PRIORITY)
+                    > current.priority.unwrap_or(DEFAULT_PRIORITY)
+            }
+            SitemapMergeStrategy::FIRST => false,
+        }
+    }
+}
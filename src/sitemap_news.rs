@@ -0,0 +1,118 @@
+use std::time::SystemTime;
+
+use crate::sitemap_datetime::{parse_timestamp, system_time_to_timestamp};
+
+/// The maximum number of URLs a single news sitemap may contain.
+pub const MAX_URLS_PER_NEWS_SITEMAP: usize = 1_000;
+
+/// How old a news article may be to stay in a news sitemap, in seconds
+/// (two days).
+const MAX_NEWS_AGE: i64 = 2 * 24 * 60 * 60;
+
+/// Represents a news article attached to a [`crate::SitemapUrl`]
+/// (Google News sitemap extension).
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapNews, SitemapUrl};
+///
+/// let mut url = SitemapUrl::new("https://example.com/business/article55.html");
+/// url.news = Some(SitemapNews::new(
+///     "The Example Times",
+///     "en",
+///     "2008-12-23",
+///     "Companies A, B in Merger Talks",
+/// ));
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SitemapNews {
+    /// The name of the news publication, as it appears on the site.
+    pub publication_name: String,
+
+    /// The language of the publication, as an ISO 639 code (e.g. `en`,
+    /// `zh-cn`).
+    pub publication_language: String,
+
+    /// The article publication date, in W3C Datetime format.
+    pub publication_date: String,
+
+    /// The title of the article.
+    pub title: String,
+}
+
+impl SitemapNews {
+    /// Creates a new `SitemapNews` entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `publication_name` - The name of the news publication.
+    /// * `publication_language` - The language of the publication.
+    /// * `publication_date` - The article publication date.
+    /// * `title` - The title of the article.
+    pub fn new(
+        publication_name: &str,
+        publication_language: &str,
+        publication_date: &str,
+        title: &str,
+    ) -> SitemapNews {
+        SitemapNews {
+            publication_name: publication_name.to_string(),
+            publication_language: publication_language.to_string(),
+            publication_date: publication_date.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    /// Returns `true` if the article was published at most two days before
+    /// `now`.
+    ///
+    /// Articles whose `publication_date` cannot be parsed as a W3C Datetime
+    /// are never considered recent.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use sitemap_writer::SitemapNews;
+    ///
+    /// let news = SitemapNews::new("The Example Times", "en", "2024-01-10T12:00:00Z", "Title");
+    /// let now = UNIX_EPOCH + Duration::from_secs(1_705_000_000); // 2024-01-11T19:06:40Z
+    /// assert!(news.is_recent(now));
+    /// ```
+    pub fn is_recent(&self, now: SystemTime) -> bool {
+        match parse_timestamp(&self.publication_date) {
+            Some(published) => published >= system_time_to_timestamp(now) - MAX_NEWS_AGE,
+            None => false,
+        }
+    }
+}
+
+/// Renders a single `<news:news>` element.
+pub(crate) fn render_news(news: &SitemapNews) -> String {
+    let mut row = "<news:news><news:publication>".to_string();
+    row += format!(
+        "<news:name>{}</news:name>",
+        html_escape::encode_text(news.publication_name.as_str())
+    )
+    .as_str();
+    row += format!(
+        "<news:language>{}</news:language>",
+        html_escape::encode_text(news.publication_language.as_str())
+    )
+    .as_str();
+    row += "</news:publication>";
+    row += format!(
+        "<news:publication_date>{}</news:publication_date>",
+        html_escape::encode_text(news.publication_date.as_str())
+    )
+    .as_str();
+    row += format!(
+        "<news:title>{}</news:title>",
+        html_escape::encode_text(news.title.as_str())
+    )
+    .as_str();
+    row += "</news:news>";
+    row
+}
@@ -0,0 +1,322 @@
+use std::collections::HashSet;
+
+use crate::sitemap_merge::{SitemapMergeStrategy, SitemapMerger};
+use crate::sitemap_url::SitemapUrl;
+
+/// Query parameters added by analytics and ad platforms, removed by
+/// [`SitemapNormalizer::strip_tracking`]. A trailing `*` matches any suffix.
+const TRACKING_PARAMS: &[&str] = &[
+    "utm_*", "gclid", "dclid", "gbraid", "wbraid", "fbclid", "msclkid", "yclid", "mc_cid",
+    "mc_eid", "igshid", "_ga", "_gl",
+];
+
+/// What [`SitemapNormalizer`] does with a trailing slash on the path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SitemapTrailingSlash {
+    /// Leaves the path as it is.
+    #[default]
+    KEEP,
+    /// Adds a trailing slash, unless the last segment looks like a file
+    /// name with an extension (e.g. `page.html`).
+    ADD,
+    /// Removes the trailing slash, except for the root path `/`.
+    REMOVE,
+}
+
+/// A configurable normalizer that maps equivalent URLs to one canonical
+/// `loc`.
+///
+/// By default it lowercases the scheme and host, removes the default port
+/// (`:80` for `http`, `:443` for `https`), strips tracking parameters such as
+/// `utm_source`, sorts the remaining query parameters by name and removes the
+/// fragment. An empty path always becomes `/`. The trailing slash is kept as
+/// it is unless a [`SitemapTrailingSlash`] policy is set.
+///
+/// Strings that are not absolute URLs are returned unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapNormalizer, SitemapTrailingSlash};
+///
+/// let normalizer = SitemapNormalizer::new().trailing_slash(SitemapTrailingSlash::ADD);
+/// assert_eq!(normalizer.normalize("https://Example.com/a"), "https://example.com/a/");
+/// assert_eq!(normalizer.normalize("HTTPS://example.com:443/a/"), "https://example.com/a/");
+/// assert_eq!(
+///     normalizer.normalize("https://example.com/a?utm_source=x&b=2&a=1#top"),
+///     "https://example.com/a/?a=1&b=2"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct SitemapNormalizer {
+    lowercase: bool,
+    remove_default_port: bool,
+    trailing_slash: SitemapTrailingSlash,
+    strip_tracking: bool,
+    strip_params: Vec<String>,
+    sort_query: bool,
+    remove_fragment: bool,
+}
+
+impl Default for SitemapNormalizer {
+    fn default() -> Self {
+        SitemapNormalizer::new()
+    }
+}
+
+impl SitemapNormalizer {
+    /// Creates a normalizer with the default rules.
+    pub fn new() -> SitemapNormalizer {
+        SitemapNormalizer {
+            lowercase: true,
+            remove_default_port: true,
+            trailing_slash: SitemapTrailingSlash::KEEP,
+            strip_tracking: true,
+            strip_params: vec![],
+            sort_query: true,
+            remove_fragment: true,
+        }
+    }
+
+    /// Enables or disables lowercasing the scheme and host.
+    pub fn lowercase(mut self, lowercase: bool) -> SitemapNormalizer {
+        self.lowercase = lowercase;
+        self
+    }
+
+    /// Enables or disables removing `:80` from `http` and `:443` from
+    /// `https` URLs.
+    pub fn remove_default_port(mut self, remove: bool) -> SitemapNormalizer {
+        self.remove_default_port = remove;
+        self
+    }
+
+    /// Sets the trailing slash policy.
+    pub fn trailing_slash(mut self, policy: SitemapTrailingSlash) -> SitemapNormalizer {
+        self.trailing_slash = policy;
+        self
+    }
+
+    /// Enables or disables stripping common tracking parameters (`utm_*`,
+    /// `gclid`, `fbclid`, ...).
+    pub fn strip_tracking(mut self, strip: bool) -> SitemapNormalizer {
+        self.strip_tracking = strip;
+        self
+    }
+
+    /// Strips an additional query parameter. A trailing `*` matches any
+    /// suffix, e.g. `ref_*`. Names are compared case-insensitively.
+    pub fn strip_param(mut self, name: &str) -> SitemapNormalizer {
+        self.strip_params.push(name.to_ascii_lowercase());
+        self
+    }
+
+    /// Enables or disables sorting query parameters by name. Parameters
+    /// with the same name keep their relative order.
+    pub fn sort_query(mut self, sort: bool) -> SitemapNormalizer {
+        self.sort_query = sort;
+        self
+    }
+
+    /// Enables or disables removing the `#fragment`.
+    pub fn remove_fragment(mut self, remove: bool) -> SitemapNormalizer {
+        self.remove_fragment = remove;
+        self
+    }
+
+    /// Returns the canonical form of a URL.
+    pub fn normalize(&self, loc: &str) -> String {
+        let loc = loc.trim();
+        let Some((scheme, rest)) = loc.split_once("://") else {
+            return loc.to_string();
+        };
+        if scheme.is_empty()
+            || !scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+        {
+            return loc.to_string();
+        }
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (rest, None),
+        };
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(slash) => rest.split_at(slash),
+            None => (rest, ""),
+        };
+
+        let scheme = if self.lowercase {
+            scheme.to_ascii_lowercase()
+        } else {
+            scheme.to_string()
+        };
+        let mut normalized = format!("{}://{}", scheme, self.authority(&scheme, authority));
+        normalized += &self.path(path);
+        if let Some(query) = query {
+            let query = self.query(query);
+            if !query.is_empty() {
+                normalized.push('?');
+                normalized += &query;
+            }
+        }
+        if let Some(fragment) = fragment.filter(|_| !self.remove_fragment) {
+            normalized.push('#');
+            normalized += fragment;
+        }
+        normalized
+    }
+
+    /// Normalizes the `loc` of each URL and removes the resulting
+    /// duplicates, using the given strategy to pick the entry to keep.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapMergeStrategy, SitemapNormalizer, SitemapUrl};
+    ///
+    /// let urls = SitemapNormalizer::new().dedup(
+    ///     vec![
+    ///         SitemapUrl::new("https://Example.com/a"),
+    ///         SitemapUrl::new("https://example.com/a?utm_source=x"),
+    ///     ],
+    ///     SitemapMergeStrategy::FIRST,
+    /// );
+    /// assert_eq!(urls.len(), 1);
+    /// assert_eq!(urls[0].loc, "https://example.com/a");
+    /// ```
+    pub fn dedup(&self, urls: Vec<SitemapUrl>, strategy: SitemapMergeStrategy) -> Vec<SitemapUrl> {
+        let mut merger = SitemapMerger::new(strategy);
+        for mut url in urls {
+            url.loc = self.normalize(&url.loc);
+            merger.push(url);
+        }
+        merger.into_urls()
+    }
+
+    fn authority(&self, scheme: &str, authority: &str) -> String {
+        let (userinfo, host_port) = match authority.rsplit_once('@') {
+            Some((userinfo, host_port)) => (Some(userinfo), host_port),
+            None => (None, authority),
+        };
+        // The port follows the last colon, unless it is inside an IPv6
+        // literal such as `[::1]`.
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, Some(port)),
+            _ => (host_port, None),
+        };
+        let port = port.filter(|port| {
+            let scheme = scheme.to_ascii_lowercase();
+            let default = matches!((scheme.as_str(), *port), ("http", "80") | ("https", "443"));
+            let redundant = port.is_empty() || (self.remove_default_port && default);
+            !redundant
+        });
+
+        let mut authority = String::new();
+        if let Some(userinfo) = userinfo {
+            authority += userinfo;
+            authority.push('@');
+        }
+        if self.lowercase {
+            authority += &host.to_ascii_lowercase();
+        } else {
+            authority += host;
+        }
+        if let Some(port) = port {
+            authority.push(':');
+            authority += port;
+        }
+        authority
+    }
+
+    fn path(&self, path: &str) -> String {
+        if path.is_empty() || path == "/" {
+            return "/".to_string();
+        }
+        match self.trailing_slash {
+            SitemapTrailingSlash::KEEP => path.to_string(),
+            SitemapTrailingSlash::ADD => {
+                let last = path.rsplit('/').next().unwrap_or_default();
+                if path.ends_with('/') || last.contains('.') {
+                    path.to_string()
+                } else {
+                    format!("{}/", path)
+                }
+            }
+            SitemapTrailingSlash::REMOVE => {
+                let trimmed = path.trim_end_matches('/');
+                if trimmed.is_empty() {
+                    "/".to_string()
+                } else {
+                    trimmed.to_string()
+                }
+            }
+        }
+    }
+
+    fn query(&self, query: &str) -> String {
+        let mut params: Vec<&str> = query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .filter(|param| {
+                let name = param
+                    .split('=')
+                    .next()
+                    .unwrap_or_default()
+                    .to_ascii_lowercase();
+                !self.is_stripped(&name)
+            })
+            .collect();
+        if self.sort_query {
+            params.sort_by_key(|param| param.split('=').next().unwrap_or_default());
+        }
+        params.join("&")
+    }
+
+    fn is_stripped(&self, name: &str) -> bool {
+        let tracking = TRACKING_PARAMS.iter().filter(|_| self.strip_tracking);
+        tracking
+            .copied()
+            .chain(self.strip_params.iter().map(String::as_str))
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == pattern,
+            })
+    }
+}
+
+/// Normalizes the URLs pushed to a writer and remembers the `loc`s already
+/// written, so that duplicates can be skipped.
+#[derive(Debug, Clone)]
+pub(crate) struct SitemapDedup {
+    normalizer: SitemapNormalizer,
+    written: HashSet<String>,
+}
+
+impl SitemapDedup {
+    pub(crate) fn new(normalizer: SitemapNormalizer) -> SitemapDedup {
+        SitemapDedup {
+            normalizer,
+            written: HashSet::new(),
+        }
+    }
+
+    /// Returns the URL with a normalized `loc`, or `None` if that `loc` was
+    /// already written.
+    pub(crate) fn prepare(&self, url: &SitemapUrl) -> Option<SitemapUrl> {
+        let loc = self.normalizer.normalize(&url.loc);
+        if self.written.contains(&loc) {
+            return None;
+        }
+        Some(SitemapUrl { loc, ..url.clone() })
+    }
+
+    /// Records that a URL was written.
+    pub(crate) fn mark_written(&mut self, loc: &str) {
+        self.written.insert(loc.to_string());
+    }
+}
@@ -0,0 +1,806 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::error::SitemapError;
+use crate::sitemap_alternate::SitemapAlternate;
+use crate::sitemap_image::SitemapImage;
+use crate::sitemap_index::SitemapIndex;
+use crate::sitemap_news::SitemapNews;
+use crate::sitemap_url::SitemapUrl;
+use crate::sitemap_video::{
+    SitemapVideo, SitemapVideoPlatform, SitemapVideoPlatforms, SitemapVideoRelationship,
+    SitemapVideoRestriction, SitemapVideoUploader,
+};
+
+/// The maximum element nesting depth accepted inside a single entry.
+const MAX_DEPTH: usize = 32;
+
+/// A single entry read from a sitemap or sitemap index.
+#[derive(Debug, Clone)]
+pub enum SitemapEntry {
+    /// A `<url>` entry of a `<urlset>`.
+    Url(SitemapUrl),
+    /// A `<sitemap>` entry of a `<sitemapindex>`.
+    Sitemap(SitemapIndex),
+}
+
+/// A streaming reader for XML sitemaps and sitemap indexes.
+///
+/// Entries are parsed one at a time, so arbitrarily large files can be read
+/// without loading them into memory. The reader yields [`SitemapEntry::Url`]
+/// for `<urlset>` documents and [`SitemapEntry::Sitemap`] for
+/// `<sitemapindex>` documents. Entities are unescaped, and the image, video,
+/// news and hreflang extensions are read back into their typed fields.
+/// Unknown elements are skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapChangeFreq, SitemapEntry, SitemapReader};
+///
+/// let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+/// <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+///   <url>
+///     <loc>https://example.com/?a=1&amp;b=2</loc>
+///     <changefreq>daily</changefreq>
+///   </url>
+/// </urlset>"#;
+///
+/// for entry in SitemapReader::new(xml.as_bytes()) {
+///     match entry.unwrap() {
+///         SitemapEntry::Url(url) => {
+///             assert_eq!(url.loc, "https://example.com/?a=1&b=2");
+///             assert_eq!(url.changefreq, Some(SitemapChangeFreq::DAILY));
+///         }
+///         SitemapEntry::Sitemap(_) => unreachable!(),
+///     }
+/// }
+/// ```
+pub struct SitemapReader<R: BufRead> {
+    reader: R,
+    line: usize,
+    column: usize,
+    root: Option<String>,
+    done: bool,
+}
+
+impl<R: BufRead> SitemapReader<R> {
+    /// Creates a reader over any [`std::io::BufRead`] source.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The XML input, e.g. `xml.as_bytes()` or a
+    ///   `BufReader<File>`.
+    pub fn new(reader: R) -> SitemapReader<R> {
+        SitemapReader {
+            reader,
+            line: 1,
+            column: 1,
+            root: None,
+            done: false,
+        }
+    }
+
+    /// Reads the next entry, or `None` once the root element is closed.
+    pub fn next_entry(&mut self) -> Result<Option<SitemapEntry>, SitemapError> {
+        if self.done {
+            return Ok(None);
+        }
+        let root = match &self.root {
+            Some(root) => root.clone(),
+            None => self.read_root()?,
+        };
+        if self.done {
+            return Ok(None);
+        }
+        loop {
+            match self.next_event()? {
+                Event::Start(start) => {
+                    let node = self.read_node(start, 0)?;
+                    match (root.as_str(), node.name.as_str()) {
+                        ("urlset", "url") => return Ok(Some(SitemapEntry::Url(to_url(&node)?))),
+                        ("sitemapindex", "sitemap") => {
+                            return Ok(Some(SitemapEntry::Sitemap(to_sitemap(&node)?)));
+                        }
+                        _ => continue,
+                    }
+                }
+                Event::End(end) => {
+                    if end.name != root {
+                        return Err(end.mismatch(&root));
+                    }
+                    self.done = true;
+                    return Ok(None);
+                }
+                Event::Text(_) => continue,
+                Event::Eof => {
+                    return Err(
+                        self.error(&format!("unexpected end of input, missing </{}>", root))
+                    );
+                }
+            }
+        }
+    }
+
+    /// Reads all remaining `<url>` entries of a `<urlset>` document.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`SitemapError::Parse`] if the document is a sitemap index
+    /// or is malformed.
+    pub fn read_urls(mut self) -> Result<Vec<SitemapUrl>, SitemapError> {
+        let mut urls = vec![];
+        while let Some(entry) = self.next_entry()? {
+            match entry {
+                SitemapEntry::Url(url) => urls.push(url),
+                SitemapEntry::Sitemap(_) => {
+                    return Err(self.error("expected <urlset>, found <sitemapindex>"));
+                }
+            }
+        }
+        if self.root.as_deref() != Some("urlset") {
+            return Err(self.error("expected <urlset>, found <sitemapindex>"));
+        }
+        Ok(urls)
+    }
+
+    /// Reads all remaining `<sitemap>` entries of a `<sitemapindex>` document.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`SitemapError::Parse`] if the document is a `<urlset>` or is
+    /// malformed.
+    pub fn read_sitemaps(mut self) -> Result<Vec<SitemapIndex>, SitemapError> {
+        let mut sitemaps = vec![];
+        while let Some(entry) = self.next_entry()? {
+            match entry {
+                SitemapEntry::Sitemap(sitemap) => sitemaps.push(sitemap),
+                SitemapEntry::Url(_) => {
+                    return Err(self.error("expected <sitemapindex>, found <urlset>"));
+                }
+            }
+        }
+        if self.root.as_deref() != Some("sitemapindex") {
+            return Err(self.error("expected <sitemapindex>, found <urlset>"));
+        }
+        Ok(sitemaps)
+    }
+
+    /// Reads the root element like [`SitemapReader::next_entry`], but returns
+    /// `None` instead of an error when it is neither `<urlset>` nor
+    /// `<sitemapindex>`, e.g. for an RSS or Atom feed.
+    pub(crate) fn read_sitemap_root(&mut self) -> Result<Option<String>, SitemapError> {
+        if let Some(root) = &self.root {
+            return Ok(Some(root.clone()));
+        }
+        let start = self.read_root_start()?;
+        if start.name != "urlset" && start.name != "sitemapindex" {
+            self.done = true;
+            return Ok(None);
+        }
+        Ok(Some(self.set_root(start)))
+    }
+
+    fn read_root(&mut self) -> Result<String, SitemapError> {
+        let start = self.read_root_start()?;
+        if start.name != "urlset" && start.name != "sitemapindex" {
+            return Err(SitemapError::Parse {
+                line: start.line,
+                column: start.column,
+                message: format!(
+                    "expected <urlset> or <sitemapindex>, found <{}>",
+                    start.name
+                ),
+            });
+        }
+        Ok(self.set_root(start))
+    }
+
+    fn set_root(&mut self, start: Start) -> String {
+        if start.empty {
+            self.done = true;
+        }
+        self.root = Some(start.name.clone());
+        start.name
+    }
+
+    fn read_root_start(&mut self) -> Result<Start, SitemapError> {
+        loop {
+            match self.next_event()? {
+                Event::Start(start) => return Ok(start),
+                Event::Text(text) if text.trim().is_empty() => continue,
+                Event::Text(_) => return Err(self.error("text outside the root element")),
+                Event::End(end) => {
+                    return Err(SitemapError::Parse {
+                        line: end.line,
+                        column: end.column,
+                        message: format!("unexpected </{}>", end.name),
+                    });
+                }
+                Event::Eof => return Err(self.error("no root element")),
+            }
+        }
+    }
+
+    fn read_node(&mut self, start: Start, depth: usize) -> Result<Node, SitemapError> {
+        if depth > MAX_DEPTH {
+            return Err(SitemapError::Parse {
+                line: start.line,
+                column: start.column,
+                message: "elements are nested too deeply".to_string(),
+            });
+        }
+        let mut node = Node {
+            name: start.name,
+            attrs: start.attrs,
+            text: String::new(),
+            children: vec![],
+            line: start.line,
+            column: start.column,
+        };
+        if start.empty {
+            return Ok(node);
+        }
+        loop {
+            match self.next_event()? {
+                Event::Start(child) => node.children.push(self.read_node(child, depth + 1)?),
+                Event::Text(text) => node.text.push_str(&text),
+                Event::End(end) => {
+                    if end.name != node.name {
+                        return Err(end.mismatch(&node.name));
+                    }
+                    return Ok(node);
+                }
+                Event::Eof => {
+                    return Err(self.error(&format!(
+                        "unexpected end of input, missing </{}>",
+                        node.name
+                    )));
+                }
+            }
+        }
+    }
+
+    fn next_event(&mut self) -> Result<Event, SitemapError> {
+        let byte = match self.peek()? {
+            Some(byte) => byte,
+            None => return Ok(Event::Eof),
+        };
+        if byte != b'<' {
+            let mut text = vec![];
+            while let Some(byte) = self.peek()? {
+                if byte == b'<' {
+                    break;
+                }
+                self.bump();
+                text.push(byte);
+            }
+            let text = self.utf8(text)?;
+            return Ok(Event::Text(self.unescape(&text)?));
+        }
+
+        let line = self.line;
+        let column = self.column;
+        self.bump();
+        match self.peek()? {
+            Some(b'?') => {
+                self.skip_until(b"?>")?;
+                self.next_event()
+            }
+            Some(b'!') => {
+                self.bump();
+                if self.eat(b"--")? {
+                    self.skip_until(b"-->")?;
+                    self.next_event()
+                } else if self.eat(b"[CDATA[")? {
+                    let text = self.read_until(b"]]>")?;
+                    Ok(Event::Text(self.utf8(text)?))
+                } else {
+                    self.skip_declaration()?;
+                    self.next_event()
+                }
+            }
+            Some(b'/') => {
+                self.bump();
+                let name = self.read_name()?;
+                self.skip_whitespace()?;
+                self.expect(b'>')?;
+                Ok(Event::End(End { name, line, column }))
+            }
+            _ => {
+                let name = self.read_name()?;
+                let mut attrs = vec![];
+                loop {
+                    self.skip_whitespace()?;
+                    match self.peek()? {
+                        Some(b'>') => {
+                            self.bump();
+                            break;
+                        }
+                        Some(b'/') => {
+                            self.bump();
+                            self.expect(b'>')?;
+                            return Ok(Event::Start(Start {
+                                name,
+                                attrs,
+                                empty: true,
+                                line,
+                                column,
+                            }));
+                        }
+                        Some(_) => attrs.push(self.read_attribute()?),
+                        None => return Err(self.error("unexpected end of input in tag")),
+                    }
+                }
+                Ok(Event::Start(Start {
+                    name,
+                    attrs,
+                    empty: false,
+                    line,
+                    column,
+                }))
+            }
+        }
+    }
+
+    fn read_attribute(&mut self) -> Result<(String, String), SitemapError> {
+        let name = self.read_name()?;
+        self.skip_whitespace()?;
+        self.expect(b'=')?;
+        self.skip_whitespace()?;
+        let quote = match self.peek()? {
+            Some(quote @ (b'"' | b'\'')) => quote,
+            _ => return Err(self.error("expected a quoted attribute value")),
+        };
+        self.bump();
+        let mut value = vec![];
+        loop {
+            match self.peek()? {
+                Some(byte) if byte == quote => {
+                    self.bump();
+                    break;
+                }
+                Some(b'<') => return Err(self.error("'<' in attribute value")),
+                Some(byte) => {
+                    self.bump();
+                    value.push(byte);
+                }
+                None => return Err(self.error("unexpected end of input in attribute")),
+            }
+        }
+        let value = self.utf8(value)?;
+        Ok((name, self.unescape(&value)?))
+    }
+
+    /// Reads an element or attribute name and returns its local part
+    /// (without namespace prefix).
+    fn read_name(&mut self) -> Result<String, SitemapError> {
+        let mut name = vec![];
+        while let Some(byte) = self.peek()? {
+            if byte.is_ascii_whitespace() || matches!(byte, b'>' | b'/' | b'=' | b'<') {
+                break;
+            }
+            self.bump();
+            name.push(byte);
+        }
+        if name.is_empty() {
+            return Err(self.error("expected a name"));
+        }
+        let name = self.utf8(name)?;
+        match name.rsplit_once(':') {
+            Some((_, local)) if !name.starts_with("xmlns") => Ok(local.to_string()),
+            _ => Ok(name),
+        }
+    }
+
+    fn unescape(&self, text: &str) -> Result<String, SitemapError> {
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('&') {
+            result.push_str(&rest[..start]);
+            let end = match rest[start..].find(';') {
+                Some(end) => start + end,
+                None => return Err(self.error("unterminated entity reference")),
+            };
+            let entity = &rest[start + 1..end];
+            let decoded = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => {
+                    let code = if let Some(hex) = entity.strip_prefix("#x") {
+                        u32::from_str_radix(hex, 16).ok()
+                    } else if let Some(dec) = entity.strip_prefix('#') {
+                        dec.parse().ok()
+                    } else {
+                        None
+                    };
+                    code.and_then(char::from_u32)
+                }
+            };
+            match decoded {
+                Some(c) => result.push(c),
+                None => return Err(self.error(&format!("unknown entity &{};", entity))),
+            }
+            rest = &rest[end + 1..];
+        }
+        result.push_str(rest);
+        Ok(result)
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, SitemapError> {
+        match self.reader.fill_buf() {
+            Ok(buf) => Ok(buf.first().copied()),
+            Err(e) => Err(SitemapError::Read(e.to_string())),
+        }
+    }
+
+    /// Consumes the byte returned by the last `peek`.
+    fn bump(&mut self) {
+        let newline = matches!(self.reader.fill_buf(), Ok(buf) if buf.first() == Some(&b'\n'));
+        self.reader.consume(1);
+        if newline {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, SitemapError> {
+        let byte = self.peek()?;
+        if byte.is_some() {
+            self.bump();
+        }
+        Ok(byte)
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), SitemapError> {
+        match self.next_byte()? {
+            Some(byte) if byte == expected => Ok(()),
+            _ => Err(self.error(&format!("expected '{}'", expected as char))),
+        }
+    }
+
+    /// Consumes `token` if the input continues with it. Only the first byte is
+    /// peeked; a partial match is an error.
+    fn eat(&mut self, token: &[u8]) -> Result<bool, SitemapError> {
+        if self.peek()? != Some(token[0]) {
+            return Ok(false);
+        }
+        for &expected in token {
+            self.expect(expected)?;
+        }
+        Ok(true)
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), SitemapError> {
+        while let Some(byte) = self.peek()? {
+            if !byte.is_ascii_whitespace() {
+                break;
+            }
+            self.bump();
+        }
+        Ok(())
+    }
+
+    fn skip_until(&mut self, terminator: &[u8]) -> Result<(), SitemapError> {
+        self.read_until(terminator).map(|_| ())
+    }
+
+    fn read_until(&mut self, terminator: &[u8]) -> Result<Vec<u8>, SitemapError> {
+        let mut bytes = vec![];
+        loop {
+            match self.next_byte()? {
+                Some(byte) => bytes.push(byte),
+                None => return Err(self.error("unexpected end of input")),
+            }
+            if bytes.ends_with(terminator) {
+                bytes.truncate(bytes.len() - terminator.len());
+                return Ok(bytes);
+            }
+        }
+    }
+
+    /// Skips a `<!DOCTYPE ...>` style declaration, including an internal
+    /// subset in square brackets.
+    fn skip_declaration(&mut self) -> Result<(), SitemapError> {
+        let mut brackets = 0;
+        loop {
+            match self.next_byte()? {
+                Some(b'[') => brackets += 1,
+                Some(b']') => brackets -= 1,
+                Some(b'>') if brackets <= 0 => return Ok(()),
+                Some(_) => {}
+                None => return Err(self.error("unexpected end of input in declaration")),
+            }
+        }
+    }
+
+    fn utf8(&self, bytes: Vec<u8>) -> Result<String, SitemapError> {
+        match String::from_utf8(bytes) {
+            Ok(text) => Ok(text),
+            Err(_) => Err(self.error("invalid UTF-8")),
+        }
+    }
+
+    fn error(&self, message: &str) -> SitemapError {
+        SitemapError::Parse {
+            line: self.line,
+            column: self.column,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl SitemapReader<Box<dyn BufRead>> {
+    /// Opens a sitemap or sitemap index file.
+    ///
+    /// With the `gzip` feature enabled, files ending in `.gz` are
+    /// decompressed transparently.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::SitemapReader;
+    ///
+    /// let urls = SitemapReader::open("sitemap.xml").unwrap().read_urls().unwrap();
+    /// ```
+    pub fn open(path: &str) -> Result<SitemapReader<Box<dyn BufRead>>, SitemapError> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) => return Err(SitemapError::FileOpen(e.to_string())),
+        };
+        #[cfg(feature = "gzip")]
+        if path.ends_with(".gz") {
+            let decoder = flate2::read::GzDecoder::new(file);
+            return Ok(SitemapReader::new(Box::new(BufReader::new(decoder))));
+        }
+        Ok(SitemapReader::new(Box::new(BufReader::new(file))))
+    }
+}
+
+impl<R: BufRead> Iterator for SitemapReader<R> {
+    type Item = Result<SitemapEntry, SitemapError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_entry() {
+            Ok(entry) => entry.map(Ok),
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+enum Event {
+    Start(Start),
+    End(End),
+    Text(String),
+    Eof,
+}
+
+struct Start {
+    name: String,
+    attrs: Vec<(String, String)>,
+    empty: bool,
+    line: usize,
+    column: usize,
+}
+
+struct End {
+    name: String,
+    line: usize,
+    column: usize,
+}
+
+impl End {
+    fn mismatch(&self, expected: &str) -> SitemapError {
+        SitemapError::Parse {
+            line: self.line,
+            column: self.column,
+            message: format!("expected </{}>, found </{}>", expected, self.name),
+        }
+    }
+}
+
+/// An element subtree, holding a single entry.
+struct Node {
+    name: String,
+    attrs: Vec<(String, String)>,
+    text: String,
+    children: Vec<Node>,
+    line: usize,
+    column: usize,
+}
+
+impl Node {
+    fn value(&self) -> String {
+        self.text.trim().to_string()
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn error(&self, message: &str) -> SitemapError {
+        SitemapError::Parse {
+            line: self.line,
+            column: self.column,
+            message: message.to_string(),
+        }
+    }
+
+    fn parse<T: std::str::FromStr>(&self) -> Result<T, SitemapError> {
+        match self.value().parse() {
+            Ok(value) => Ok(value),
+            Err(_) => Err(self.error(&format!("invalid <{}> value: {}", self.name, self.value()))),
+        }
+    }
+
+    /// Parses a `<priority>`, which must be a number from 0.0 to 1.0.
+    fn priority(&self) -> Result<f32, SitemapError> {
+        let priority: f32 = self.parse()?;
+        if !(0.0..=1.0).contains(&priority) {
+            return Err(self.error(&format!("invalid <{}> value: {}", self.name, self.value())));
+        }
+        Ok(priority)
+    }
+
+    fn yes_no(&self) -> Result<bool, SitemapError> {
+        match self.value().to_ascii_lowercase().as_str() {
+            "yes" => Ok(true),
+            "no" => Ok(false),
+            _ => Err(self.error(&format!("invalid <{}> value: {}", self.name, self.value()))),
+        }
+    }
+
+    fn relationship(&self) -> Result<SitemapVideoRelationship, SitemapError> {
+        match self.attr("relationship") {
+            Some("allow") => Ok(SitemapVideoRelationship::ALLOW),
+            Some("deny") => Ok(SitemapVideoRelationship::DENY),
+            _ => Err(self.error(&format!("invalid <{}> relationship", self.name))),
+        }
+    }
+}
+
+fn to_url(node: &Node) -> Result<SitemapUrl, SitemapError> {
+    let mut url = SitemapUrl::default();
+    let mut has_loc = false;
+    for child in &node.children {
+        match child.name.as_str() {
+            "loc" => {
+                url.loc = child.value();
+                has_loc = true;
+            }
+            "lastmod" => url.lastmod = Some(child.value()),
+            "changefreq" => url.changefreq = Some(child.parse()?),
+            "priority" => url.priority = Some(child.priority()?),
+            "image" => url.images.push(to_image(child)),
+            "video" => url.videos.push(to_video(child)?),
+            "news" => url.news = Some(to_news(child)),
+            "link" if child.attr("rel") == Some("alternate") => {
+                url.alternates.push(SitemapAlternate::new(
+                    child.attr("hreflang").unwrap_or_default(),
+                    child.attr("href").unwrap_or_default(),
+                ));
+            }
+            _ => {}
+        }
+    }
+    if !has_loc {
+        return Err(node.error("<url> without <loc>"));
+    }
+    Ok(url)
+}
+
+fn to_sitemap(node: &Node) -> Result<SitemapIndex, SitemapError> {
+    let mut sitemap = SitemapIndex::default();
+    let mut has_loc = false;
+    for child in &node.children {
+        match child.name.as_str() {
+            "loc" => {
+                sitemap.loc = child.value();
+                has_loc = true;
+            }
+            "lastmod" => sitemap.lastmod = Some(child.value()),
+            _ => {}
+        }
+    }
+    if !has_loc {
+        return Err(node.error("<sitemap> without <loc>"));
+    }
+    Ok(sitemap)
+}
+
+fn to_image(node: &Node) -> SitemapImage {
+    let mut image = SitemapImage::default();
+    for child in &node.children {
+        match child.name.as_str() {
+            "loc" => image.loc = child.value(),
+            "caption" => image.caption = Some(child.value()),
+            "title" => image.title = Some(child.value()),
+            "license" => image.license = Some(child.value()),
+            _ => {}
+        }
+    }
+    image
+}
+
+fn to_video(node: &Node) -> Result<SitemapVideo, SitemapError> {
+    let mut video = SitemapVideo::default();
+    for child in &node.children {
+        match child.name.as_str() {
+            "thumbnail_loc" => video.thumbnail_loc = child.value(),
+            "title" => video.title = child.value(),
+            "description" => video.description = child.value(),
+            "content_loc" => video.content_loc = Some(child.value()),
+            "player_loc" => video.player_loc = Some(child.value()),
+            "duration" => video.duration = Some(child.parse()?),
+            "expiration_date" => video.expiration_date = Some(child.value()),
+            "rating" => video.rating = Some(child.parse()?),
+            "view_count" => video.view_count = Some(child.parse()?),
+            "publication_date" => video.publication_date = Some(child.value()),
+            "family_friendly" => video.family_friendly = Some(child.yes_no()?),
+            "restriction" => {
+                video.restriction = Some(SitemapVideoRestriction {
+                    relationship: child.relationship()?,
+                    countries: child.text.split_whitespace().map(String::from).collect(),
+                });
+            }
+            "platform" => {
+                let mut platforms = vec![];
+                for platform in child.text.split_whitespace() {
+                    platforms.push(match platform.to_ascii_lowercase().as_str() {
+                        "web" => SitemapVideoPlatform::WEB,
+                        "mobile" => SitemapVideoPlatform::MOBILE,
+                        "tv" => SitemapVideoPlatform::TV,
+                        _ => return Err(child.error(&format!("invalid platform: {}", platform))),
+                    });
+                }
+                video.platform = Some(SitemapVideoPlatforms {
+                    relationship: child.relationship()?,
+                    platforms,
+                });
+            }
+            "requires_subscription" => video.requires_subscription = Some(child.yes_no()?),
+            "uploader" => {
+                video.uploader = Some(SitemapVideoUploader {
+                    name: child.value(),
+                    info: child.attr("info").map(String::from),
+                });
+            }
+            "live" => video.live = Some(child.yes_no()?),
+            "tag" => video.tags.push(child.value()),
+            _ => {}
+        }
+    }
+    Ok(video)
+}
+
+fn to_news(node: &Node) -> SitemapNews {
+    let mut news = SitemapNews::default();
+    for child in &node.children {
+        match child.name.as_str() {
+            "publication" => {
+                for field in &child.children {
+                    match field.name.as_str() {
+                        "name" => news.publication_name = field.value(),
+                        "language" => news.publication_language = field.value(),
+                        _ => {}
+                    }
+                }
+            }
+            "publication_date" => news.publication_date = child.value(),
+            "title" => news.title = child.value(),
+            _ => {}
+        }
+    }
+    news
+}
@@ -0,0 +1,395 @@
+use std::fs;
+use std::io::{ErrorKind, Write};
+
+use crate::error::SitemapError;
+use crate::sitemap_file::SitemapFile;
+
+/// A single `Allow` or `Disallow` rule of a robots.txt group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SitemapRobotsRule {
+    /// `Allow: <path>`
+    ALLOW(String),
+    /// `Disallow: <path>`
+    DISALLOW(String),
+}
+
+/// A robots.txt group: one or more `User-agent` lines followed by their
+/// rules.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::SitemapRobotsGroup;
+///
+/// let group = SitemapRobotsGroup::new("*")
+///     .disallow("/admin/")
+///     .allow("/admin/public/");
+/// assert_eq!(group.user_agents, vec!["*"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SitemapRobotsGroup {
+    /// The crawlers the group applies to, e.g. `*` or `Googlebot`.
+    pub user_agents: Vec<String>,
+
+    /// The rules of the group, in order.
+    pub rules: Vec<SitemapRobotsRule>,
+}
+
+impl SitemapRobotsGroup {
+    /// Creates a new group for a single user agent.
+    pub fn new(user_agent: &str) -> SitemapRobotsGroup {
+        SitemapRobotsGroup {
+            user_agents: vec![user_agent.to_string()],
+            rules: vec![],
+        }
+    }
+
+    /// Adds another user agent to the group.
+    pub fn user_agent(mut self, user_agent: &str) -> SitemapRobotsGroup {
+        self.user_agents.push(user_agent.to_string());
+        self
+    }
+
+    /// Adds an `Allow` rule.
+    pub fn allow(mut self, path: &str) -> SitemapRobotsGroup {
+        self.rules.push(SitemapRobotsRule::ALLOW(path.to_string()));
+        self
+    }
+
+    /// Adds a `Disallow` rule.
+    pub fn disallow(mut self, path: &str) -> SitemapRobotsGroup {
+        self.rules
+            .push(SitemapRobotsRule::DISALLOW(path.to_string()));
+        self
+    }
+
+    /// Returns `true` if the group applies to the same set of user agents,
+    /// ignoring case and order.
+    fn same_agents(&self, user_agents: &[String]) -> bool {
+        let mut ours: Vec<String> = self
+            .user_agents
+            .iter()
+            .map(|agent| agent.to_ascii_lowercase())
+            .collect();
+        let mut theirs: Vec<String> = user_agents
+            .iter()
+            .map(|agent| agent.to_ascii_lowercase())
+            .collect();
+        ours.sort();
+        ours.dedup();
+        theirs.sort();
+        theirs.dedup();
+        ours == theirs
+    }
+
+    fn render(&self) -> Vec<String> {
+        let mut lines = self.render_agents();
+        lines.extend(self.render_rules());
+        lines
+    }
+
+    fn render_agents(&self) -> Vec<String> {
+        self.user_agents
+            .iter()
+            .map(|agent| format!("User-agent: {}", agent))
+            .collect()
+    }
+
+    fn render_rules(&self) -> Vec<String> {
+        if self.rules.is_empty() {
+            // A group needs at least one rule; an empty Disallow allows all.
+            return vec!["Disallow:".to_string()];
+        }
+        self.rules
+            .iter()
+            .map(|rule| match rule {
+                SitemapRobotsRule::ALLOW(path) => format!("Allow: {}", path),
+                SitemapRobotsRule::DISALLOW(path) => format!("Disallow: {}", path),
+            })
+            .collect()
+    }
+}
+
+/// A robots.txt builder with `Sitemap:` directives.
+///
+/// [`SitemapRobots::build`] renders a complete file, while
+/// [`SitemapRobots::update`] merges the groups and sitemaps into an existing
+/// robots.txt: groups for the same user agents get their `Allow`/`Disallow`
+/// rules replaced, other groups, directives and comments are kept where they
+/// are, and `Sitemap:` lines are only added when missing. `\r\n` line
+/// endings are preserved.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapRobots, SitemapRobotsGroup};
+///
+/// let robots = SitemapRobots::new()
+///     .group(SitemapRobotsGroup::new("*").disallow("/admin/"))
+///     .sitemap("https://example.com/sitemap_index.xml");
+///
+/// assert_eq!(
+///     robots.build().unwrap(),
+///     "User-agent: *\nDisallow: /admin/\n\nSitemap: https://example.com/sitemap_index.xml\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SitemapRobots {
+    /// The user-agent groups, in order.
+    pub groups: Vec<SitemapRobotsGroup>,
+
+    /// The absolute URLs of sitemaps and sitemap indexes.
+    pub sitemaps: Vec<String>,
+}
+
+impl SitemapRobots {
+    /// Creates an empty robots.txt builder.
+    pub fn new() -> SitemapRobots {
+        SitemapRobots::default()
+    }
+
+    /// Adds a user-agent group.
+    pub fn group(mut self, group: SitemapRobotsGroup) -> SitemapRobots {
+        self.groups.push(group);
+        self
+    }
+
+    /// Adds a `Sitemap:` directive for a sitemap or sitemap index URL.
+    pub fn sitemap(mut self, url: &str) -> SitemapRobots {
+        self.sitemaps.push(url.to_string());
+        self
+    }
+
+    /// Renders the robots.txt content.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`SitemapError::InvalidValue`] if a user agent, path or
+    /// sitemap URL contains a line break.
+    pub fn build(&self) -> Result<String, SitemapError> {
+        self.update_str("")
+    }
+
+    /// Writes the robots.txt file at the specified path, replacing any
+    /// existing file.
+    pub fn make(&self, path: &str) -> Result<(), SitemapError> {
+        write_file(path, &self.build()?)
+    }
+
+    /// Merges the groups and sitemaps into the robots.txt file at the
+    /// specified path, creating it if it does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::SitemapRobots;
+    ///
+    /// let result = SitemapRobots::new()
+    ///     .sitemap("https://example.com/sitemap_index.xml")
+    ///     .update("public/robots.txt");
+    /// assert!(result.is_ok());
+    /// ```
+    pub fn update(&self, path: &str) -> Result<(), SitemapError> {
+        let existing = match fs::read_to_string(path) {
+            Ok(existing) => existing,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(SitemapError::Read(e.to_string())),
+        };
+        write_file(path, &self.update_str(&existing)?)
+    }
+
+    /// Merges the groups and sitemaps into existing robots.txt content.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapRobots, SitemapRobotsGroup};
+    ///
+    /// let existing = "User-agent: *\nDisallow: /old/\nCrawl-delay: 5\n\nUser-agent: BadBot\nDisallow: /\n";
+    /// let updated = SitemapRobots::new()
+    ///     .group(SitemapRobotsGroup::new("*").disallow("/new/"))
+    ///     .sitemap("https://example.com/sitemap.xml")
+    ///     .update_str(existing)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     updated,
+    ///     "User-agent: *\nDisallow: /new/\nCrawl-delay: 5\n\nUser-agent: BadBot\nDisallow: /\n\nSitemap: https://example.com/sitemap.xml\n"
+    /// );
+    /// ```
+    pub fn update_str(&self, existing: &str) -> Result<String, SitemapError> {
+        self.check()?;
+        let lines: Vec<&str> = existing.lines().collect();
+        let spans = group_spans(&lines);
+
+        let mut out: Vec<String> = vec![];
+        let mut merged = vec![false; self.groups.len()];
+        let mut next = 0;
+        for span in &spans {
+            out.extend(lines[next..span.start].iter().map(|line| line.to_string()));
+            next = span.end;
+            let replacement = self
+                .groups
+                .iter()
+                .position(|group| group.same_agents(&span.user_agents));
+            let Some(index) = replacement else {
+                out.extend(
+                    lines[span.start..span.end]
+                        .iter()
+                        .map(|line| line.to_string()),
+                );
+                continue;
+            };
+            merged[index] = true;
+            // The new user agents and rules take the place of the first old
+            // ones; everything else, such as comments, Crawl-delay and
+            // Sitemap lines, stays where it was.
+            let group = &self.groups[index];
+            out.extend(group.render_agents());
+            let agents_end = out.len();
+            let mut rules = Some(group.render_rules());
+            for line in &lines[span.start..span.end] {
+                match directive(line).map(|(key, _)| key).as_deref() {
+                    Some("user-agent") => {}
+                    Some("allow" | "disallow") => out.extend(rules.take().unwrap_or_default()),
+                    _ => out.push(line.to_string()),
+                }
+            }
+            if let Some(rules) = rules {
+                out.splice(agents_end..agents_end, rules);
+            }
+        }
+        out.extend(lines[next..].iter().map(|line| line.to_string()));
+
+        for (group, _) in self.groups.iter().zip(merged).filter(|(_, merged)| !merged) {
+            push_block(&mut out, group.render());
+        }
+
+        let existing_sitemaps: Vec<&str> = lines
+            .iter()
+            .filter_map(|line| directive(line))
+            .filter(|(key, _)| key == "sitemap")
+            .map(|(_, value)| value)
+            .collect();
+        let mut sitemaps: Vec<String> = vec![];
+        for url in &self.sitemaps {
+            if !existing_sitemaps.contains(&url.as_str()) && !sitemaps.contains(url) {
+                sitemaps.push(url.clone());
+            }
+        }
+        push_block(
+            &mut out,
+            sitemaps
+                .iter()
+                .map(|url| format!("Sitemap: {}", url))
+                .collect(),
+        );
+
+        // Keep the line endings of the existing file.
+        let newline = if existing.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        let mut content = out.join(newline);
+        if !content.is_empty() {
+            content.push_str(newline);
+        }
+        Ok(content)
+    }
+
+    fn check(&self) -> Result<(), SitemapError> {
+        let mut values: Vec<&str> = self.sitemaps.iter().map(String::as_str).collect();
+        for group in &self.groups {
+            values.extend(group.user_agents.iter().map(String::as_str));
+            values.extend(group.rules.iter().map(|rule| match rule {
+                SitemapRobotsRule::ALLOW(path) | SitemapRobotsRule::DISALLOW(path) => path.as_str(),
+            }));
+        }
+        match values.iter().find(|value| value.contains(['\n', '\r'])) {
+            Some(value) => Err(SitemapError::InvalidValue(format!(
+                "robots.txt value contains a line break: {:?}",
+                value
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The lines of one group in an existing robots.txt.
+struct GroupSpan {
+    start: usize,
+    end: usize,
+    user_agents: Vec<String>,
+}
+
+/// Finds the groups of an existing robots.txt.
+///
+/// A group starts at a `User-agent` line that does not directly follow
+/// another one, and runs until the next group.
+fn group_spans(lines: &[&str]) -> Vec<GroupSpan> {
+    let mut spans: Vec<GroupSpan> = vec![];
+    let mut in_agents = false;
+    for (index, line) in lines.iter().enumerate() {
+        let Some((key, value)) = directive(line) else {
+            continue;
+        };
+        if key == "user-agent" {
+            match spans.last_mut() {
+                Some(span) if in_agents => span.user_agents.push(value.to_string()),
+                _ => {
+                    if let Some(span) = spans.last_mut() {
+                        span.end = index;
+                    }
+                    spans.push(GroupSpan {
+                        start: index,
+                        end: lines.len(),
+                        user_agents: vec![value.to_string()],
+                    });
+                }
+            }
+            in_agents = true;
+        } else {
+            in_agents = false;
+        }
+    }
+    // Trailing blank lines separate the last group from what follows.
+    if let Some(span) = spans.last_mut() {
+        while span.end > span.start && lines[span.end - 1].trim().is_empty() {
+            span.end -= 1;
+        }
+    }
+    spans
+}
+
+/// Splits a line into its lowercase key and its value, without comments.
+fn directive(line: &str) -> Option<(String, &str)> {
+    let line = match line.split_once('#') {
+        Some((line, _)) => line,
+        None => line,
+    };
+    let (key, value) = line.split_once(':')?;
+    Some((key.trim().to_ascii_lowercase(), value.trim()))
+}
+
+/// Appends a block of lines, separated from the previous content by a blank
+/// line.
+fn push_block(out: &mut Vec<String>, block: Vec<String>) {
+    if block.is_empty() {
+        return;
+    }
+    while out.last().is_some_and(|line| line.trim().is_empty()) {
+        out.pop();
+    }
+    if !out.is_empty() {
+        out.push(String::new());
+    }
+    out.extend(block);
+}
+
+fn write_file(path: &str, content: &str) -> Result<(), SitemapError> {
+    let mut file = SitemapFile::create(path)?;
+    if let Err(e) = file.write_all(content.as_bytes()) {
+        return Err(SitemapError::Write(e.to_string()));
+    }
+    file.close()
+}
@@ -0,0 +1,245 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::error::SitemapError;
+use crate::sitemap_datetime::SitemapLastmod;
+use crate::sitemap_format::SitemapFormat;
+use crate::sitemap_sharded::join_url;
+use crate::sitemap_url::SitemapUrl;
+use crate::sitemap_writer::SitemapWriter;
+
+/// A scanner that builds a sitemap from the HTML files of a static site.
+///
+/// Every `*.html` file below the directory becomes a URL under the base URL:
+/// `about.html` maps to `<base>/about.html`, and `index.html` maps to the
+/// directory itself with a trailing slash (`blog/index.html` becomes
+/// `<base>/blog/`). `lastmod` is taken from the file modification time.
+/// Hidden files and directories (starting with `.`) are skipped. Symbolic
+/// links are followed, except a link to a directory that is already being
+/// walked, which would otherwise loop forever. Dangling links are skipped.
+///
+/// Include and exclude patterns are matched against the path relative to the
+/// directory, using `/` as separator. `*` matches within a path segment,
+/// `**` matches across segments and `?` matches a single character.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sitemap_writer::SitemapScanner;
+///
+/// let result = SitemapScanner::new("public", "https://example.com/")
+///     .exclude("404.html")
+///     .exclude("drafts/**")
+///     .make("public/sitemap.xml");
+/// assert!(result.is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SitemapScanner {
+    dir: String,
+    base_url: String,
+    includes: Vec<String>,
+    excludes: Vec<String>,
+}
+
+impl SitemapScanner {
+    /// Creates a new scanner.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The output directory of the static site (e.g. `public`).
+    /// * `base_url` - The public URL of `dir` (e.g. `https://example.com/`).
+    pub fn new(dir: &str, base_url: &str) -> SitemapScanner {
+        SitemapScanner {
+            dir: dir.to_string(),
+            base_url: base_url.to_string(),
+            includes: vec![],
+            excludes: vec![],
+        }
+    }
+
+    /// Only includes HTML files matching the pattern (e.g. `blog/**`).
+    ///
+    /// When called several times, files matching any of the patterns are
+    /// included. Without include patterns, all HTML files are.
+    pub fn include(mut self, pattern: &str) -> SitemapScanner {
+        self.includes.push(pattern.to_string());
+        self
+    }
+
+    /// Excludes files matching the pattern (e.g. `404.html` or `drafts/**`).
+    ///
+    /// Exclude patterns take precedence over include patterns.
+    pub fn exclude(mut self, pattern: &str) -> SitemapScanner {
+        self.excludes.push(pattern.to_string());
+        self
+    }
+
+    /// Walks the directory and returns the URLs, sorted by path.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`SitemapError::Read`] if the directory or a file's
+    /// metadata cannot be read, or if the modification time of an included
+    /// HTML file is not available.
+    pub fn scan(&self) -> Result<Vec<SitemapUrl>, SitemapError> {
+        let mut files: Vec<(String, fs::Metadata)> = vec![];
+        walk(Path::new(&self.dir), "", &mut HashSet::new(), &mut files)?;
+
+        let mut urls = vec![];
+        for (path, metadata) in files {
+            if !path.ends_with(".html") || !self.matches(&path) {
+                continue;
+            }
+            let lastmod = SitemapLastmod::from(metadata.modified().map_err(|e| {
+                SitemapError::Read(format!(
+                    "{}: {}",
+                    Path::new(&self.dir).join(&path).display(),
+                    e
+                ))
+            })?);
+            let path = match path.strip_suffix("index.html") {
+                Some(dir) if dir.is_empty() || dir.ends_with('/') => dir,
+                _ => path.as_str(),
+            };
+            let mut url = SitemapUrl::new(&join_url(&self.base_url, &encode_path(path)));
+            url.set_lastmod(lastmod);
+            urls.push(url);
+        }
+        Ok(urls)
+    }
+
+    /// Scans the directory and writes the sitemap to the specified path.
+    pub fn make(&self, path: &str) -> Result<(), SitemapError> {
+        SitemapWriter::make(path, self.scan()?)
+    }
+
+    /// Scans the directory and writes the sitemap to the specified path,
+    /// laid out with the given format.
+    pub fn make_with_format(&self, path: &str, format: SitemapFormat) -> Result<(), SitemapError> {
+        SitemapWriter::make_with_format(path, self.scan()?, format)
+    }
+
+    /// Scans the directory and builds the sitemap XML string.
+    pub fn build(&self) -> Result<String, SitemapError> {
+        Ok(SitemapWriter::build(self.scan()?))
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        let included = self.includes.is_empty()
+            || self
+                .includes
+                .iter()
+                .any(|pattern| glob_match(pattern, path));
+        included
+            && !self
+                .excludes
+                .iter()
+                .any(|pattern| glob_match(pattern, path))
+    }
+}
+
+/// Collects the files below `dir` with their metadata, as paths relative to
+/// the scanned directory.
+///
+/// `ancestors` holds the canonical paths of the directories being walked, so
+/// a symbolic link back to one of them is not followed.
+fn walk(
+    dir: &Path,
+    prefix: &str,
+    ancestors: &mut HashSet<PathBuf>,
+    files: &mut Vec<(String, fs::Metadata)>,
+) -> Result<(), SitemapError> {
+    let read_error = |e: std::io::Error| SitemapError::Read(format!("{}: {}", dir.display(), e));
+    let canonical = fs::canonicalize(dir).map_err(read_error)?;
+    if !ancestors.insert(canonical.clone()) {
+        return Ok(());
+    }
+    let mut entries = fs::read_dir(dir)
+        .map_err(read_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(read_error)?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = format!("{}{}", prefix, name);
+        // Follows symbolic links, like a web server would.
+        let metadata = match fs::metadata(entry.path()) {
+            Ok(metadata) => metadata,
+            // A dangling link points nowhere a web server could serve.
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(read_error(e)),
+        };
+        if metadata.is_dir() {
+            walk(&entry.path(), &format!("{}/", path), ancestors, files)?;
+        } else {
+            files.push((path, metadata));
+        }
+    }
+    ancestors.remove(&canonical);
+    Ok(())
+}
+
+/// Percent-encodes the characters that are not allowed in a URL path.
+pub(crate) fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~!$&'()*+,;=:@".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Matches a `/`-separated path against a glob pattern.
+///
+/// `*` and `?` do not match `/`, `**` matches any number of characters
+/// including `/`, and `**/` also matches no directory at all.
+pub(crate) fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    glob_match_chars(&pattern, &path, &mut HashMap::new())
+}
+
+/// Matches the rest of a pattern against the rest of a path.
+///
+/// Results are memoized by the remaining lengths, so patterns with several
+/// wildcards, like `**/**/**/*a`, do not backtrack exponentially.
+fn glob_match_chars(
+    pattern: &[char],
+    path: &[char],
+    memo: &mut HashMap<(usize, usize), bool>,
+) -> bool {
+    if let Some(&matched) = memo.get(&(pattern.len(), path.len())) {
+        return matched;
+    }
+    let matched = match pattern {
+        [] => path.is_empty(),
+        ['*', '*', '/', rest @ ..] => {
+            glob_match_chars(rest, path, memo)
+                || (0..path.len())
+                    .filter(|&i| path[i] == '/')
+                    .any(|i| glob_match_chars(rest, &path[i + 1..], memo))
+        }
+        ['*', '*', rest @ ..] => (0..=path.len()).any(|i| glob_match_chars(rest, &path[i..], memo)),
+        ['*', rest @ ..] => {
+            let segment = path.iter().position(|&c| c == '/').unwrap_or(path.len());
+            (0..=segment).any(|i| glob_match_chars(rest, &path[i..], memo))
+        }
+        ['?', rest @ ..] => {
+            !path.is_empty() && path[0] != '/' && glob_match_chars(rest, &path[1..], memo)
+        }
+        [c, rest @ ..] => {
+            !path.is_empty() && path[0] == *c && glob_match_chars(rest, &path[1..], memo)
+        }
+    };
+    memo.insert((pattern.len(), path.len()), matched);
+    matched
+}
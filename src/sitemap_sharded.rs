@@ -0,0 +1,306 @@
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::error::SitemapError;
+use crate::sitemap_datetime::parse_timestamp;
+use crate::sitemap_file::SitemapFile;
+use crate::sitemap_format::SitemapFormat;
+use crate::sitemap_index::{SitemapIndex, SitemapIndexWriter};
+use crate::sitemap_news::MAX_URLS_PER_NEWS_SITEMAP;
+use crate::sitemap_normalize::{SitemapDedup, SitemapNormalizer};
+use crate::sitemap_url::SitemapUrl;
+use crate::sitemap_writer::{
+    MAX_SITEMAP_BYTES, MAX_URLS_PER_SITEMAP, SitemapNamespaces, SitemapWriter, URLSET_CLOSE,
+    check_url, check_valid, render_url,
+};
+
+/// A writer that splits URLs across multiple sitemap files and generates a
+/// sitemap index for them.
+///
+/// A new shard is started whenever the current one would exceed 50,000 URLs
+/// or 50 MB of uncompressed XML. Shard files are named by replacing `{}` in
+/// the file name pattern with a 1-based counter (`sitemap-1.xml`,
+/// `sitemap-2.xml`, ...).
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use sitemap_writer::{ShardedSitemapWriter, SitemapUrl};
+///
+/// let mut writer = ShardedSitemapWriter::new(
+///     "public/sitemaps",
+///     "sitemap-{}.xml",
+///     "https://example.com/sitemaps/",
+/// ).unwrap();
+/// for id in 0..200_000 {
+///     writer.push(&SitemapUrl::new(&format!("https://example.com/items/{}", id))).unwrap();
+/// }
+/// let sitemaps = writer.finish("sitemap_index.xml").unwrap();
+/// assert_eq!(sitemaps.len(), 4);
+/// ```
+pub struct ShardedSitemapWriter {
+    dir: String,
+    pattern: String,
+    base_url: String,
+    max_urls: usize,
+    max_bytes: usize,
+    namespaces: SitemapNamespaces,
+    format: SitemapFormat,
+    news_cutoff: Option<SystemTime>,
+    dedup: Option<SitemapDedup>,
+    strict: bool,
+    entry_count: usize,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+    current: Option<SitemapWriter<SitemapFile>>,
+    current_lastmod: Option<String>,
+    sitemaps: Vec<SitemapIndex>,
+}
+
+impl ShardedSitemapWriter {
+    /// Creates a new sharded writer.
+    ///
+    /// The output directory is created if it does not exist yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory where shard files and the index are written.
+    /// * `pattern` - The shard file name, containing `{}` as a placeholder
+    ///   for the shard number (e.g. `sitemap-{}.xml`).
+    /// * `base_url` - The public URL of `dir`, used to build each shard's
+    ///   `<loc>` in the index (e.g. `https://example.com/sitemaps/`).
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`SitemapError::InvalidPattern`] if `pattern` has no `{}`
+    /// placeholder, or [`SitemapError::FileOpen`] if the directory cannot be
+    /// created.
+    pub fn new(
+        dir: &str,
+        pattern: &str,
+        base_url: &str,
+    ) -> Result<ShardedSitemapWriter, SitemapError> {
+        if !pattern.contains("{}") {
+            return Err(SitemapError::InvalidPattern(pattern.to_string()));
+        }
+        if let Err(e) = fs::create_dir_all(dir) {
+            return Err(SitemapError::FileOpen(e.to_string()));
+        }
+        Ok(ShardedSitemapWriter {
+            dir: dir.to_string(),
+            pattern: pattern.to_string(),
+            base_url: base_url.to_string(),
+            max_urls: MAX_URLS_PER_SITEMAP,
+            max_bytes: MAX_SITEMAP_BYTES,
+            namespaces: SitemapNamespaces::default(),
+            format: SitemapFormat::default(),
+            news_cutoff: None,
+            dedup: None,
+            strict: false,
+            entry_count: 0,
+            #[cfg(feature = "gzip")]
+            gzip: false,
+            current: None,
+            current_lastmod: None,
+            sitemaps: vec![],
+        })
+    }
+
+    /// Lowers the per-shard limits.
+    ///
+    /// Values above the protocol limits (50,000 URLs, 50 MB) are capped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::ShardedSitemapWriter;
+    ///
+    /// let writer = ShardedSitemapWriter::new("out", "sitemap-{}.xml", "https://example.com/")
+    ///     .unwrap()
+    ///     .with_limits(10_000, 10 * 1024 * 1024);
+    /// ```
+    pub fn with_limits(mut self, max_urls: usize, max_bytes: usize) -> ShardedSitemapWriter {
+        self.max_urls = max_urls.clamp(1, MAX_URLS_PER_SITEMAP);
+        self.max_bytes = max_bytes.min(MAX_SITEMAP_BYTES);
+        self
+    }
+
+    /// Declares extension namespaces on every shard's `<urlset>` element.
+    ///
+    /// This is required before pushing URLs that use an extension, such as
+    /// images. When `news` is declared, shards roll over every 1,000 URLs.
+    pub fn with_namespaces(mut self, namespaces: SitemapNamespaces) -> ShardedSitemapWriter {
+        self.namespaces = namespaces;
+        self
+    }
+
+    /// Lays out the shards and the index with the given format.
+    ///
+    /// The size limit is measured on the laid out XML, so pretty-printed
+    /// shards hold fewer URLs before rolling over.
+    pub fn with_format(mut self, format: SitemapFormat) -> ShardedSitemapWriter {
+        self.format = format;
+        self
+    }
+
+    /// Enables or disables strict mode, refusing invalid entries with
+    /// [`SitemapError::InvalidEntry`]. See [`SitemapWriter::strict`].
+    pub fn strict(mut self, strict: bool) -> ShardedSitemapWriter {
+        self.strict = strict;
+        self
+    }
+
+    /// Silently skips news articles published more than two days before
+    /// `now`. See [`SitemapWriter::skip_stale_news`].
+    pub fn skip_stale_news(mut self, now: SystemTime) -> ShardedSitemapWriter {
+        self.news_cutoff = Some(now);
+        self
+    }
+
+    /// Normalizes the `loc` of each URL before writing it, and silently
+    /// skips URLs whose normalized `loc` was already written to any shard.
+    /// See [`SitemapWriter::normalize`].
+    pub fn normalize(mut self, normalizer: SitemapNormalizer) -> ShardedSitemapWriter {
+        self.dedup = Some(SitemapDedup::new(normalizer));
+        self
+    }
+
+    /// Enables or disables gzip compression of the shard files.
+    ///
+    /// The size limit is still measured on the uncompressed XML. The pattern
+    /// should usually end in `.xml.gz` when compression is enabled. The index
+    /// itself is written uncompressed.
+    ///
+    /// Requires the `gzip` feature.
+    #[cfg(feature = "gzip")]
+    pub fn with_gzip(mut self, gzip: bool) -> ShardedSitemapWriter {
+        self.gzip = gzip;
+        self
+    }
+
+    /// Writes a URL to the current shard, starting a new shard first if the
+    /// URL would not fit.
+    pub fn push(&mut self, url: &SitemapUrl) -> Result<(), SitemapError> {
+        let index = self.entry_count;
+        self.entry_count += 1;
+        if let (Some(now), Some(news)) = (self.news_cutoff, &url.news)
+            && !news.is_recent(now)
+        {
+            return Ok(());
+        }
+        let normalized = match &self.dedup {
+            Some(dedup) => match dedup.prepare(url) {
+                Some(normalized) => Some(normalized),
+                None => return Ok(()),
+            },
+            None => None,
+        };
+        let url = normalized.as_ref().unwrap_or(url);
+        if self.strict {
+            check_valid(url, index)?;
+        }
+        check_url(url, &self.namespaces)?;
+        let row = self.format.element(&render_url(url), 1).into_owned();
+        if let Some(writer) = &self.current {
+            let close = self.format.line(URLSET_CLOSE, 0);
+            let full = writer.url_count() >= self.url_limit()
+                || writer.bytes_written() + row.len() + close.len() > self.max_bytes;
+            if full {
+                self.close_shard()?;
+            }
+        }
+        if self.current.is_none() {
+            self.open_shard()?;
+        }
+
+        if let Some(lastmod) = &url.lastmod {
+            let newer = match &self.current_lastmod {
+                Some(current) => parse_timestamp(lastmod) > parse_timestamp(current),
+                None => true,
+            };
+            if newer {
+                self.current_lastmod = Some(lastmod.clone());
+            }
+        }
+        if let Some(writer) = self.current.as_mut() {
+            writer.push_laid_out_row(&row)?;
+        }
+        if let Some(dedup) = &mut self.dedup {
+            dedup.mark_written(&url.loc);
+        }
+        Ok(())
+    }
+
+    /// Closes the last shard and writes the sitemap index.
+    ///
+    /// Each index entry's `lastmod` is the newest `lastmod` of the URLs in
+    /// that shard, or `None` if none of them had one.
+    ///
+    /// # Arguments
+    ///
+    /// * `index_name` - The file name of the index, written inside `dir`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the [`SitemapIndex`] entries written to the index.
+    pub fn finish(mut self, index_name: &str) -> Result<Vec<SitemapIndex>, SitemapError> {
+        self.close_shard()?;
+        let path = Path::new(&self.dir).join(index_name);
+        SitemapIndexWriter::make_with_format(
+            &path.to_string_lossy(),
+            self.sitemaps.clone(),
+            self.format,
+        )?;
+        Ok(self.sitemaps)
+    }
+
+    fn url_limit(&self) -> usize {
+        if self.namespaces.news {
+            self.max_urls.min(MAX_URLS_PER_NEWS_SITEMAP)
+        } else {
+            self.max_urls
+        }
+    }
+
+    fn open_shard(&mut self) -> Result<(), SitemapError> {
+        let name = self
+            .pattern
+            .replace("{}", &(self.sitemaps.len() + 1).to_string());
+        let path = Path::new(&self.dir).join(&name);
+        #[cfg(feature = "gzip")]
+        let file = if self.gzip {
+            SitemapFile::create_gz(&path)?
+        } else {
+            SitemapFile::create(&path)?
+        };
+        #[cfg(not(feature = "gzip"))]
+        let file = SitemapFile::create(&path)?;
+        self.current = Some(SitemapWriter::with_format(
+            file,
+            self.namespaces,
+            self.format,
+        )?);
+        self.sitemaps
+            .push(SitemapIndex::new(&join_url(&self.base_url, &name)));
+        Ok(())
+    }
+
+    fn close_shard(&mut self) -> Result<(), SitemapError> {
+        if let Some(writer) = self.current.take() {
+            writer.finish()?.close()?;
+            if let Some(sitemap) = self.sitemaps.last_mut() {
+                sitemap.lastmod = self.current_lastmod.take();
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn join_url(base_url: &str, name: &str) -> String {
+    if base_url.ends_with('/') {
+        format!("{}{}", base_url, name)
+    } else {
+        format!("{}/{}", base_url, name)
+    }
+}
@@ -0,0 +1,177 @@
+use std::fs::File;
+use std::io::Write;
+
+use crate::error::SitemapError;
+use crate::sitemap_file::SitemapFile;
+use crate::sitemap_url::SitemapUrl;
+use crate::sitemap_writer::{MAX_SITEMAP_BYTES, MAX_URLS_PER_SITEMAP};
+
+/// A writer for plain-text sitemaps: a UTF-8 file with one URL per line.
+///
+/// Only the `loc` of each [`SitemapUrl`] is written; all other fields,
+/// including extensions, are ignored. The protocol limits of 50,000 URLs and
+/// 50 MB per file are enforced, and URLs containing line breaks are refused.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapTextWriter, SitemapUrl};
+///
+/// let mut writer = SitemapTextWriter::new(Vec::new());
+/// writer.push(&SitemapUrl::new("https://example.com/")).unwrap();
+/// writer.push(&SitemapUrl::new("https://example.com/about/")).unwrap();
+/// let text = String::from_utf8(writer.finish().unwrap()).unwrap();
+/// assert_eq!(text, "https://example.com/\nhttps://example.com/about/\n");
+/// ```
+pub struct SitemapTextWriter<W: Write = File> {
+    writer: W,
+    url_count: usize,
+    bytes_written: usize,
+}
+
+impl<W: Write> SitemapTextWriter<W> {
+    /// Starts a new text sitemap on the given writer.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Any [`std::io::Write`] sink.
+    pub fn new(writer: W) -> SitemapTextWriter<W> {
+        SitemapTextWriter {
+            writer,
+            url_count: 0,
+            bytes_written: 0,
+        }
+    }
+
+    /// Writes the `loc` of a URL as a single line.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error without writing anything if:
+    ///
+    /// * `loc` is empty or contains a line break
+    ///   ([`SitemapError::InvalidValue`]),
+    /// * the file already holds 50,000 URLs or would grow beyond 50 MB
+    ///   ([`SitemapError::LimitExceeded`]).
+    pub fn push(&mut self, url: &SitemapUrl) -> Result<(), SitemapError> {
+        if url.loc.is_empty() {
+            return Err(SitemapError::InvalidValue("empty URL".to_string()));
+        }
+        if url.loc.contains('\n') || url.loc.contains('\r') {
+            return Err(SitemapError::InvalidValue(format!(
+                "URL contains a line break: {:?}",
+                url.loc
+            )));
+        }
+        if self.url_count >= MAX_URLS_PER_SITEMAP {
+            return Err(SitemapError::LimitExceeded(format!(
+                "a sitemap may contain at most {} URLs",
+                MAX_URLS_PER_SITEMAP
+            )));
+        }
+        if self.bytes_written + url.loc.len() + 1 > MAX_SITEMAP_BYTES {
+            return Err(SitemapError::LimitExceeded(format!(
+                "a sitemap may be at most {} bytes",
+                MAX_SITEMAP_BYTES
+            )));
+        }
+        self.write_text(&url.loc)?;
+        self.write_text("\n")?;
+        self.url_count += 1;
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, SitemapError> {
+        match self.writer.flush() {
+            Ok(_) => Ok(self.writer),
+            Err(e) => Err(SitemapError::Write(e.to_string())),
+        }
+    }
+
+    /// Returns the number of URLs written so far.
+    pub fn url_count(&self) -> usize {
+        self.url_count
+    }
+
+    /// Returns the number of bytes written so far.
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    fn write_text(&mut self, str: &str) -> Result<(), SitemapError> {
+        if let Err(e) = self.writer.write_all(str.as_bytes()) {
+            return Err(SitemapError::Write(e.to_string()));
+        }
+        self.bytes_written += str.len();
+        Ok(())
+    }
+}
+
+impl SitemapTextWriter {
+    /// Creates a text sitemap file (e.g. `sitemap.txt`) at the specified
+    /// path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file path where the sitemap will be written.
+    /// * `urls` - A vector of [`SitemapUrl`] whose `loc` values are written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::{SitemapTextWriter, SitemapUrl};
+    ///
+    /// let result = SitemapTextWriter::make("sitemap.txt", vec![
+    ///     SitemapUrl::new("https://example.com/"),
+    /// ]);
+    /// assert!(result.is_ok());
+    /// ```
+    pub fn make(path: &str, urls: Vec<SitemapUrl>) -> Result<(), SitemapError> {
+        write_file(SitemapFile::create(path)?, &urls)
+    }
+
+    /// Creates a gzip-compressed text sitemap file (e.g. `sitemap.txt.gz`)
+    /// at the specified path.
+    ///
+    /// Requires the `gzip` feature.
+    #[cfg(feature = "gzip")]
+    pub fn make_gz(path: &str, urls: Vec<SitemapUrl>) -> Result<(), SitemapError> {
+        write_file(SitemapFile::create_gz(path)?, &urls)
+    }
+
+    /// Builds a text sitemap string from the provided URLs.
+    ///
+    /// Unlike [`crate::SitemapWriter::build`], this method checks its input,
+    /// because a line break in a URL would corrupt the file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapTextWriter, SitemapUrl};
+    ///
+    /// let text = SitemapTextWriter::build(vec![SitemapUrl::new("https://example.com/")]);
+    /// assert_eq!(text.unwrap(), "https://example.com/\n");
+    ///
+    /// let text = SitemapTextWriter::build(vec![SitemapUrl::new("https://example.com/\nhttps://evil.com/")]);
+    /// assert!(text.is_err());
+    /// ```
+    pub fn build(urls: Vec<SitemapUrl>) -> Result<String, SitemapError> {
+        let mut writer = SitemapTextWriter::new(Vec::new());
+        for url in &urls {
+            writer.push(url)?;
+        }
+        match String::from_utf8(writer.finish()?) {
+            Ok(text) => Ok(text),
+            Err(e) => Err(SitemapError::Write(e.to_string())),
+        }
+    }
+}
+
+fn write_file(file: SitemapFile, urls: &[SitemapUrl]) -> Result<(), SitemapError> {
+    let mut writer = SitemapTextWriter::new(file);
+    for url in urls {
+        writer.push(url)?;
+    }
+    writer.finish()?.close()
+}
@@ -1,4 +1,13 @@
 use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::SitemapError;
+use crate::sitemap_alternate::SitemapAlternate;
+use crate::sitemap_datetime::SitemapLastmod;
+use crate::sitemap_image::SitemapImage;
+use crate::sitemap_news::SitemapNews;
+use crate::sitemap_video::SitemapVideo;
+use crate::validation::{SitemapIssue, validate_lastmod, validate_loc};
 
 /// Represents a single URL entry in a sitemap.
 ///
@@ -14,6 +23,7 @@ use std::fmt::{Debug, Display, Formatter};
 ///     lastmod: Some("2024-01-15".to_string()),
 ///     changefreq: Some(SitemapChangeFreq::WEEKLY),
 ///     priority: Some(0.8),
+///     ..SitemapUrl::default()
 /// };
 /// ```
 ///
@@ -25,6 +35,7 @@ use std::fmt::{Debug, Display, Formatter};
 /// let url = SitemapUrl::new("https://example.com/page");
 /// ```
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SitemapUrl {
     /// The URL of the page. This is the only required field.
     ///
@@ -34,18 +45,72 @@ pub struct SitemapUrl {
     /// The date of last modification of the page.
     ///
     /// Should be in W3C Datetime format (e.g., `2024-01-15` or `2024-01-15T12:00:00+00:00`).
+    /// Use [`SitemapUrl::set_lastmod`] to set it from a typed date.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub lastmod: Option<String>,
 
     /// How frequently the page is likely to change.
     ///
     /// This value provides general information to search engines and may not
     /// correlate exactly to how often they crawl the page.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub changefreq: Option<SitemapChangeFreq>,
 
     /// The priority of this URL relative to other URLs on your site.
     ///
     /// Valid values range from 0.0 to 1.0. The default priority of a page is 0.5.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub priority: Option<f32>,
+
+    /// Images on the page (Google image sitemap extension).
+    ///
+    /// At most 1,000 images are allowed per URL. The `xmlns:image` namespace
+    /// is only declared when at least one URL has images.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub images: Vec<SitemapImage>,
+
+    /// Videos on the page (Google video sitemap extension).
+    ///
+    /// The `xmlns:video` namespace is only declared when at least one URL
+    /// has videos.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub videos: Vec<SitemapVideo>,
+
+    /// The news article on the page (Google News sitemap extension).
+    ///
+    /// The `xmlns:news` namespace is only declared when at least one URL has
+    /// a news entry.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub news: Option<SitemapNews>,
+
+    /// Localized versions of the page, written as `xhtml:link` elements.
+    ///
+    /// The `xmlns:xhtml` namespace is only declared when at least one URL has
+    /// alternates. See [`SitemapAlternate::expand`] for building reciprocal
+    /// entries.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub alternates: Vec<SitemapAlternate>,
 }
 
 impl Default for SitemapUrl {
@@ -55,6 +120,10 @@ impl Default for SitemapUrl {
             lastmod: None,
             changefreq: None,
             priority: None,
+            images: vec![],
+            videos: vec![],
+            news: None,
+            alternates: vec![],
         }
     }
 }
@@ -62,7 +131,8 @@ impl Default for SitemapUrl {
 impl SitemapUrl {
     /// Creates a new `SitemapUrl` with only the URL specified.
     ///
-    /// All other fields (`lastmod`, `changefreq`, `priority`) will be `None`.
+    /// All other fields (`lastmod`, `changefreq`, `priority`, `news`) will be
+    /// `None` and `images`/`videos`/`alternates` will be empty.
     ///
     /// # Arguments
     ///
@@ -83,6 +153,82 @@ impl SitemapUrl {
             ..SitemapUrl::default()
         }
     }
+
+    /// Sets `lastmod` from a typed date, formatted as a W3C Datetime.
+    ///
+    /// Accepts strings, [`std::time::SystemTime`], [`crate::SitemapDateTime`]
+    /// and, with the `chrono` and `time` features, their date types.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use sitemap_writer::{SitemapDateTime, SitemapUrl};
+    ///
+    /// let mut url = SitemapUrl::new("https://example.com/");
+    /// url.set_lastmod(UNIX_EPOCH + Duration::from_secs(1_705_000_000));
+    /// assert_eq!(url.lastmod.as_deref(), Some("2024-01-11T19:06:40+00:00"));
+    ///
+    /// url.set_lastmod(SitemapDateTime::date(2024, 2, 29).unwrap());
+    /// assert_eq!(url.lastmod.as_deref(), Some("2024-02-29"));
+    /// ```
+    pub fn set_lastmod(&mut self, lastmod: impl Into<SitemapLastmod>) {
+        self.lastmod = Some(lastmod.into().to_string());
+    }
+
+    /// Returns `lastmod` parsed as a W3C Datetime.
+    ///
+    /// A value that is not a valid W3C Datetime is returned as
+    /// [`SitemapLastmod::Raw`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::SitemapUrl;
+    ///
+    /// let mut url = SitemapUrl::new("https://example.com/");
+    /// url.lastmod = Some("2024-01-15T10:30:00+09:00".to_string());
+    /// let lastmod = url.parsed_lastmod().unwrap();
+    /// assert_eq!(lastmod.timestamp(), Some(1_705_282_200));
+    /// ```
+    pub fn parsed_lastmod(&self) -> Option<SitemapLastmod> {
+        self.lastmod.as_deref().map(SitemapLastmod::from)
+    }
+
+    /// Checks the entry against the sitemap protocol.
+    ///
+    /// `loc` must be an absolute `http`/`https` URL of at most 2,048
+    /// characters, `priority` must be within `0.0..=1.0` and `lastmod` must be
+    /// a W3C Datetime.
+    ///
+    /// # Returns
+    ///
+    /// Returns every problem found, or an empty `Vec` if the entry is valid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapIssue, SitemapUrl};
+    ///
+    /// let mut url = SitemapUrl::new("/about/");
+    /// url.priority = Some(7.0);
+    /// assert_eq!(url.validate(), vec![
+    ///     SitemapIssue::InvalidLoc("/about/".to_string()),
+    ///     SitemapIssue::PriorityOutOfRange(7.0),
+    /// ]);
+    /// ```
+    pub fn validate(&self) -> Vec<SitemapIssue> {
+        let mut issues = validate_loc(&self.loc);
+        if let Some(lastmod) = &self.lastmod {
+            issues.extend(validate_lastmod(lastmod));
+        }
+        if let Some(priority) = self.priority
+            && !(0.0..=1.0).contains(&priority)
+        {
+            issues.push(SitemapIssue::PriorityOutOfRange(priority));
+        }
+        issues
+    }
 }
 
 /// Indicates how frequently the content at a URL is likely to change.
@@ -97,8 +243,13 @@ impl SitemapUrl {
 ///
 /// let freq = SitemapChangeFreq::DAILY;
 /// assert_eq!(freq.to_string(), "daily");
+///
+/// let parsed: SitemapChangeFreq = "weekly".parse().unwrap();
+/// assert_eq!(parsed, SitemapChangeFreq::WEEKLY);
 /// ```
 #[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum SitemapChangeFreq {
     /// The page changes every time it is accessed.
     ALWAYS,
@@ -136,3 +287,25 @@ impl Display for SitemapChangeFreq {
         write!(f, "{}", s)
     }
 }
+
+impl FromStr for SitemapChangeFreq {
+    type Err = SitemapError;
+
+    /// Parses the protocol string (`always`, `hourly`, ...), ignoring case
+    /// and surrounding whitespace.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "always" => Ok(SitemapChangeFreq::ALWAYS),
+            "hourly" => Ok(SitemapChangeFreq::HOURLY),
+            "daily" => Ok(SitemapChangeFreq::DAILY),
+            "weekly" => Ok(SitemapChangeFreq::WEEKLY),
+            "monthly" => Ok(SitemapChangeFreq::MONTHLY),
+            "yearly" => Ok(SitemapChangeFreq::YEARLY),
+            "never" => Ok(SitemapChangeFreq::NEVER),
+            _ => Err(SitemapError::InvalidValue(format!(
+                "unknown changefreq: {}",
+                s
+            ))),
+        }
+    }
+}
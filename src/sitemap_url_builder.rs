@@ -0,0 +1,143 @@
+use crate::error::SitemapError;
+use crate::sitemap_alternate::SitemapAlternate;
+use crate::sitemap_datetime::SitemapLastmod;
+use crate::sitemap_image::SitemapImage;
+use crate::sitemap_news::SitemapNews;
+use crate::sitemap_url::{SitemapChangeFreq, SitemapUrl};
+use crate::sitemap_video::SitemapVideo;
+use crate::sitemap_writer::{SitemapNamespaces, check_url, check_valid};
+
+/// A fluent builder for [`SitemapUrl`], created with [`SitemapUrl::builder`].
+///
+/// Page and alternate URLs can be given as anything that converts into a
+/// `String`, including `&str`, `String` and `url::Url`. Images take a
+/// [`SitemapImage`], a `&str` or a `String`, so a `url::Url` is passed as
+/// `url.as_str()`. The entry is validated by [`SitemapUrlBuilder::build`].
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapChangeFreq, SitemapUrl};
+///
+/// let url = SitemapUrl::builder("https://example.com/")
+///     .lastmod("2024-01-15")
+///     .changefreq(SitemapChangeFreq::DAILY)
+///     .priority(1.0)
+///     .image("https://example.com/logo.png")
+///     .alternate("de", "https://example.com/de/")
+///     .build()
+///     .unwrap();
+/// assert_eq!(url.images.len(), 1);
+///
+/// assert!(SitemapUrl::builder("/relative").build().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct SitemapUrlBuilder {
+    url: SitemapUrl,
+}
+
+impl SitemapUrlBuilder {
+    /// Sets the last modification date.
+    ///
+    /// Accepts a string, a [`crate::SitemapDateTime`], a
+    /// [`std::time::SystemTime`] or, with the `chrono`/`time` features, their
+    /// date types.
+    pub fn lastmod(mut self, lastmod: impl Into<SitemapLastmod>) -> SitemapUrlBuilder {
+        self.url.set_lastmod(lastmod);
+        self
+    }
+
+    /// Sets the change frequency.
+    pub fn changefreq(mut self, changefreq: SitemapChangeFreq) -> SitemapUrlBuilder {
+        self.url.changefreq = Some(changefreq);
+        self
+    }
+
+    /// Sets the priority, which must be within `0.0..=1.0`.
+    pub fn priority(mut self, priority: f32) -> SitemapUrlBuilder {
+        self.url.priority = Some(priority);
+        self
+    }
+
+    /// Adds an image, given as a [`SitemapImage`] or as its URL (`&str` or
+    /// `String`).
+    pub fn image(mut self, image: impl Into<SitemapImage>) -> SitemapUrlBuilder {
+        self.url.images.push(image.into());
+        self
+    }
+
+    /// Adds a video.
+    pub fn video(mut self, video: SitemapVideo) -> SitemapUrlBuilder {
+        self.url.videos.push(video);
+        self
+    }
+
+    /// Sets the news article.
+    pub fn news(mut self, news: SitemapNews) -> SitemapUrlBuilder {
+        self.url.news = Some(news);
+        self
+    }
+
+    /// Adds a localized alternate of the page.
+    ///
+    /// # Arguments
+    ///
+    /// * `hreflang` - The language code of the alternate page.
+    /// * `href` - The URL of the alternate page.
+    pub fn alternate(
+        mut self,
+        hreflang: impl Into<String>,
+        href: impl Into<String>,
+    ) -> SitemapUrlBuilder {
+        self.url.alternates.push(SitemapAlternate {
+            hreflang: hreflang.into(),
+            href: href.into(),
+        });
+        self
+    }
+
+    /// Validates and returns the [`SitemapUrl`].
+    ///
+    /// # Returns
+    ///
+    /// Returns an error if:
+    ///
+    /// * [`SitemapUrl::validate`] reports problems
+    ///   ([`SitemapError::InvalidEntry`]),
+    /// * there are more than 1,000 images ([`SitemapError::LimitExceeded`]),
+    /// * a video violates the video extension constraints
+    ///   ([`SitemapError::InvalidVideo`]).
+    ///
+    /// These are the checks a writer in strict mode applies.
+    pub fn build(self) -> Result<SitemapUrl, SitemapError> {
+        check_valid(&self.url, 0)?;
+        let namespaces = SitemapNamespaces::from_urls(std::slice::from_ref(&self.url));
+        check_url(&self.url, &namespaces)?;
+        Ok(self.url)
+    }
+}
+
+impl SitemapUrl {
+    /// Starts building a `SitemapUrl` with the given page URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `loc` - The URL of the page, e.g. a `&str`, `String` or `url::Url`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::SitemapUrl;
+    ///
+    /// let url = SitemapUrl::builder("https://example.com/").priority(0.8).build().unwrap();
+    /// assert_eq!(url.priority, Some(0.8));
+    /// ```
+    pub fn builder(loc: impl Into<String>) -> SitemapUrlBuilder {
+        SitemapUrlBuilder {
+            url: SitemapUrl {
+                loc: loc.into(),
+                ..SitemapUrl::default()
+            },
+        }
+    }
+}
@@ -0,0 +1,478 @@
+use std::fmt::{Display, Formatter};
+
+use crate::error::SitemapError;
+
+/// The maximum number of tags a single video may have.
+pub const MAX_VIDEO_TAGS: usize = 32;
+
+/// The maximum length of a video description, in characters.
+pub const MAX_VIDEO_DESCRIPTION_LEN: usize = 2_048;
+
+/// The maximum duration of a video, in seconds (8 hours).
+pub const MAX_VIDEO_DURATION: u32 = 28_800;
+
+/// Represents a single video attached to a [`crate::SitemapUrl`]
+/// (Google video sitemap extension).
+///
+/// `thumbnail_loc`, `title` and `description` are required, as is at least
+/// one of `content_loc` and `player_loc`. Entries violating the documented
+/// constraints are rejected by [`crate::SitemapWriter::push`] and
+/// [`crate::SitemapWriter::make`].
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapUrl, SitemapVideo};
+///
+/// let mut video = SitemapVideo::new(
+///     "https://example.com/thumbs/123.jpg",
+///     "Grilling steaks for summer",
+///     "Alkis shows you how to get perfectly done steaks every time",
+/// );
+/// video.content_loc = Some("https://example.com/video/123.mp4".to_string());
+/// video.duration = Some(600);
+/// video.tags = vec!["steak".to_string(), "summer".to_string()];
+/// assert!(video.validate().is_ok());
+///
+/// let mut url = SitemapUrl::new("https://example.com/videos/123");
+/// url.videos.push(video);
+/// ```
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SitemapVideo {
+    /// The URL of the video thumbnail image. Required.
+    pub thumbnail_loc: String,
+
+    /// The title of the video. Required.
+    pub title: String,
+
+    /// The description of the video, at most 2,048 characters. Required.
+    pub description: String,
+
+    /// The URL of the actual video media file.
+    ///
+    /// At least one of `content_loc` and `player_loc` is required.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub content_loc: Option<String>,
+
+    /// The URL of a player for the video.
+    ///
+    /// At least one of `content_loc` and `player_loc` is required.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub player_loc: Option<String>,
+
+    /// The duration of the video in seconds, from 1 to 28,800.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub duration: Option<u32>,
+
+    /// The date after which the video is no longer available, in W3C
+    /// Datetime format.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub expiration_date: Option<String>,
+
+    /// The rating of the video, from 0.0 to 5.0.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub rating: Option<f32>,
+
+    /// The number of times the video has been viewed.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub view_count: Option<u64>,
+
+    /// The date the video was first published, in W3C Datetime format.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub publication_date: Option<String>,
+
+    /// Whether the video is suitable for all audiences.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub family_friendly: Option<bool>,
+
+    /// Countries where the video may or may not be played.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub restriction: Option<SitemapVideoRestriction>,
+
+    /// Platforms where the video may or may not be played.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub platform: Option<SitemapVideoPlatforms>,
+
+    /// Whether a subscription is required to view the video.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub requires_subscription: Option<bool>,
+
+    /// The uploader of the video.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub uploader: Option<SitemapVideoUploader>,
+
+    /// Whether the video is a live stream.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub live: Option<bool>,
+
+    /// Tags describing the video, at most 32.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub tags: Vec<String>,
+}
+
+impl SitemapVideo {
+    /// Creates a new `SitemapVideo` with only the required text fields set.
+    ///
+    /// `content_loc` or `player_loc` must still be set before the video can
+    /// be written.
+    ///
+    /// # Arguments
+    ///
+    /// * `thumbnail_loc` - The URL of the video thumbnail image.
+    /// * `title` - The title of the video.
+    /// * `description` - The description of the video.
+    pub fn new(thumbnail_loc: &str, title: &str, description: &str) -> SitemapVideo {
+        SitemapVideo {
+            thumbnail_loc: thumbnail_loc.to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            ..SitemapVideo::default()
+        }
+    }
+
+    /// Checks the video against the constraints documented for the video
+    /// sitemap extension.
+    ///
+    /// # Returns
+    ///
+    /// Returns a [`SitemapError::InvalidVideo`] describing the first violated
+    /// constraint.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::SitemapVideo;
+    ///
+    /// let mut video = SitemapVideo::new("https://example.com/t.jpg", "Title", "Description");
+    /// assert!(video.validate().is_err()); // neither content_loc nor player_loc
+    ///
+    /// video.player_loc = Some("https://example.com/player?id=1".to_string());
+    /// video.duration = Some(0);
+    /// assert!(video.validate().is_err()); // duration outside 1..=28800
+    /// ```
+    pub fn validate(&self) -> Result<(), SitemapError> {
+        if self.thumbnail_loc.is_empty() {
+            return Err(invalid(self, "thumbnail_loc is required"));
+        }
+        if self.title.is_empty() {
+            return Err(invalid(self, "title is required"));
+        }
+        if self.description.is_empty() {
+            return Err(invalid(self, "description is required"));
+        }
+        if self.description.chars().count() > MAX_VIDEO_DESCRIPTION_LEN {
+            return Err(invalid(
+                self,
+                &format!(
+                    "description is longer than {} characters",
+                    MAX_VIDEO_DESCRIPTION_LEN
+                ),
+            ));
+        }
+        if self.content_loc.is_none() && self.player_loc.is_none() {
+            return Err(invalid(self, "content_loc or player_loc is required"));
+        }
+        if let Some(duration) = self.duration
+            && !(1..=MAX_VIDEO_DURATION).contains(&duration)
+        {
+            return Err(invalid(
+                self,
+                &format!(
+                    "duration {} is outside 1..={} seconds",
+                    duration, MAX_VIDEO_DURATION
+                ),
+            ));
+        }
+        if let Some(rating) = self.rating
+            && !(0.0..=5.0).contains(&rating)
+        {
+            return Err(invalid(
+                self,
+                &format!("rating {} is outside 0.0..=5.0", rating),
+            ));
+        }
+        if self.tags.len() > MAX_VIDEO_TAGS {
+            return Err(invalid(
+                self,
+                &format!(
+                    "{} tags given, at most {} are allowed",
+                    self.tags.len(),
+                    MAX_VIDEO_TAGS
+                ),
+            ));
+        }
+        if let Some(uploader) = &self.uploader
+            && uploader.name.chars().count() > 255
+        {
+            return Err(invalid(self, "uploader name is longer than 255 characters"));
+        }
+        Ok(())
+    }
+}
+
+/// Restricts the countries where a video may be played.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapVideoRelationship, SitemapVideoRestriction};
+///
+/// let restriction = SitemapVideoRestriction {
+///     relationship: SitemapVideoRelationship::ALLOW,
+///     countries: vec!["US".to_string(), "CA".to_string()],
+/// };
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SitemapVideoRestriction {
+    /// Whether the listed countries are allowed or denied.
+    pub relationship: SitemapVideoRelationship,
+
+    /// ISO 3166 country codes.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub countries: Vec<String>,
+}
+
+/// Restricts the platforms where a video may be played.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SitemapVideoPlatforms {
+    /// Whether the listed platforms are allowed or denied.
+    pub relationship: SitemapVideoRelationship,
+
+    /// The platforms the relationship applies to.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub platforms: Vec<SitemapVideoPlatform>,
+}
+
+/// The uploader of a video.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SitemapVideoUploader {
+    /// The name of the uploader, at most 255 characters.
+    pub name: String,
+
+    /// A URL with additional information about the uploader.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub info: Option<String>,
+}
+
+/// Whether a video restriction lists allowed or denied values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SitemapVideoRelationship {
+    /// Only the listed values are allowed.
+    ALLOW,
+    /// The listed values are denied.
+    DENY,
+}
+
+impl Display for SitemapVideoRelationship {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SitemapVideoRelationship::ALLOW => "allow",
+            SitemapVideoRelationship::DENY => "deny",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// A platform a video can be played on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum SitemapVideoPlatform {
+    /// Desktop and laptop browsers.
+    WEB,
+    /// Mobile browsers.
+    MOBILE,
+    /// TV browsers.
+    TV,
+}
+
+impl Display for SitemapVideoPlatform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SitemapVideoPlatform::WEB => "web",
+            SitemapVideoPlatform::MOBILE => "mobile",
+            SitemapVideoPlatform::TV => "tv",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+fn invalid(video: &SitemapVideo, msg: &str) -> SitemapError {
+    SitemapError::InvalidVideo(format!("{} ({})", msg, video.title))
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+/// Renders a single `<video:video>` element.
+pub(crate) fn render_video(video: &SitemapVideo) -> String {
+    let mut row = "<video:video>".to_string();
+    row += format!(
+        "<video:thumbnail_loc>{}</video:thumbnail_loc>",
+        html_escape::encode_text(video.thumbnail_loc.as_str())
+    )
+    .as_str();
+    row += format!(
+        "<video:title>{}</video:title>",
+        html_escape::encode_text(video.title.as_str())
+    )
+    .as_str();
+    row += format!(
+        "<video:description>{}</video:description>",
+        html_escape::encode_text(video.description.as_str())
+    )
+    .as_str();
+    if let Some(content_loc) = &video.content_loc {
+        row += format!(
+            "<video:content_loc>{}</video:content_loc>",
+            html_escape::encode_text(content_loc)
+        )
+        .as_str();
+    }
+    if let Some(player_loc) = &video.player_loc {
+        row += format!(
+            "<video:player_loc>{}</video:player_loc>",
+            html_escape::encode_text(player_loc)
+        )
+        .as_str();
+    }
+    if let Some(duration) = video.duration {
+        row += format!("<video:duration>{}</video:duration>", duration).as_str();
+    }
+    if let Some(expiration_date) = &video.expiration_date {
+        row += format!(
+            "<video:expiration_date>{}</video:expiration_date>",
+            html_escape::encode_text(expiration_date)
+        )
+        .as_str();
+    }
+    if let Some(rating) = video.rating {
+        row += format!("<video:rating>{}</video:rating>", rating).as_str();
+    }
+    if let Some(view_count) = video.view_count {
+        row += format!("<video:view_count>{}</video:view_count>", view_count).as_str();
+    }
+    if let Some(publication_date) = &video.publication_date {
+        row += format!(
+            "<video:publication_date>{}</video:publication_date>",
+            html_escape::encode_text(publication_date)
+        )
+        .as_str();
+    }
+    if let Some(family_friendly) = video.family_friendly {
+        row += format!(
+            "<video:family_friendly>{}</video:family_friendly>",
+            yes_no(family_friendly)
+        )
+        .as_str();
+    }
+    if let Some(restriction) = &video.restriction {
+        row += format!(
+            r#"<video:restriction relationship="{}">{}</video:restriction>"#,
+            restriction.relationship,
+            html_escape::encode_text(&restriction.countries.join(" "))
+        )
+        .as_str();
+    }
+    if let Some(platform) = &video.platform {
+        let platforms: Vec<String> = platform.platforms.iter().map(|p| p.to_string()).collect();
+        row += format!(
+            r#"<video:platform relationship="{}">{}</video:platform>"#,
+            platform.relationship,
+            platforms.join(" ")
+        )
+        .as_str();
+    }
+    if let Some(requires_subscription) = video.requires_subscription {
+        row += format!(
+            "<video:requires_subscription>{}</video:requires_subscription>",
+            yes_no(requires_subscription)
+        )
+        .as_str();
+    }
+    if let Some(uploader) = &video.uploader {
+        match &uploader.info {
+            Some(info) => {
+                row += format!(
+                    r#"<video:uploader info="{}">{}</video:uploader>"#,
+                    html_escape::encode_double_quoted_attribute(info),
+                    html_escape::encode_text(uploader.name.as_str())
+                )
+                .as_str()
+            }
+            None => {
+                row += format!(
+                    "<video:uploader>{}</video:uploader>",
+                    html_escape::encode_text(uploader.name.as_str())
+                )
+                .as_str()
+            }
+        }
+    }
+    if let Some(live) = video.live {
+        row += format!("<video:live>{}</video:live>", yes_no(live)).as_str();
+    }
+    for tag in &video.tags {
+        row += format!("<video:tag>{}</video:tag>", html_escape::encode_text(tag)).as_str();
+    }
+    row += "</video:video>";
+    row
+}
@@ -1,16 +1,110 @@
 use std::fs::File;
 use std::io::Write;
+use std::time::SystemTime;
+
+#[cfg(feature = "gzip")]
+use flate2::{Compression, write::GzEncoder};
 
 use crate::error::SitemapError;
+use crate::sitemap_alternate::render_alternate;
+use crate::sitemap_file::SitemapFile;
+use crate::sitemap_format::{SitemapFormat, XML_HEADER};
+use crate::sitemap_image::{MAX_IMAGES_PER_URL, render_image};
+use crate::sitemap_news::{MAX_URLS_PER_NEWS_SITEMAP, render_news};
+use crate::sitemap_normalize::{SitemapDedup, SitemapNormalizer};
 use crate::sitemap_url::SitemapUrl;
+use crate::sitemap_video::render_video;
+use crate::validation::{SitemapDiagnostic, diagnose};
+
+/// The maximum number of URLs a single sitemap may contain.
+pub const MAX_URLS_PER_SITEMAP: usize = 50_000;
+
+/// The maximum size of a single uncompressed sitemap, in bytes (50 MB).
+pub const MAX_SITEMAP_BYTES: usize = 52_428_800;
+
+const SITEMAP_NS: &str = "http://www.sitemaps.org/schemas/sitemap/0.9";
+const IMAGE_NS: &str = "http://www.google.com/schemas/sitemap-image/1.1";
+const VIDEO_NS: &str = "http://www.google.com/schemas/sitemap-video/1.1";
+const NEWS_NS: &str = "http://www.google.com/schemas/sitemap-news/0.9";
+const XHTML_NS: &str = "http://www.w3.org/1999/xhtml";
+pub(crate) const URLSET_CLOSE: &str = r#"</urlset> "#;
+
+/// The extension namespaces declared on the `<urlset>` element.
+///
+/// [`SitemapWriter::make`] and [`SitemapWriter::build`] detect the required
+/// namespaces from their input. When streaming, the namespaces must be
+/// declared up front with [`SitemapWriter::with_namespaces`], because the
+/// `<urlset>` tag is written before any entry.
+///
+/// # Examples
+///
+/// ```rust
+/// use sitemap_writer::{SitemapImage, SitemapNamespaces, SitemapUrl, SitemapWriter};
+///
+/// let namespaces = SitemapNamespaces {
+///     image: true,
+///     ..SitemapNamespaces::default()
+/// };
+/// let mut writer = SitemapWriter::with_namespaces(Vec::new(), namespaces).unwrap();
+///
+/// let mut url = SitemapUrl::new("https://example.com/");
+/// url.images.push(SitemapImage::new("https://example.com/logo.png"));
+/// writer.push(&url).unwrap();
+/// writer.finish().unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SitemapNamespaces {
+    /// Declares `xmlns:image` for the Google image extension.
+    pub image: bool,
+    /// Declares `xmlns:video` for the Google video extension.
+    pub video: bool,
+    /// Declares `xmlns:news` for the Google News extension.
+    ///
+    /// A sitemap declaring this namespace is treated as a news sitemap and
+    /// may contain at most 1,000 URLs.
+    pub news: bool,
+    /// Declares `xmlns:xhtml` for hreflang alternates.
+    pub xhtml: bool,
+}
+
+impl SitemapNamespaces {
+    /// Returns the namespaces required by the given URLs.
+    pub fn from_urls(urls: &[SitemapUrl]) -> SitemapNamespaces {
+        let mut namespaces = SitemapNamespaces::default();
+        for url in urls {
+            namespaces.image |= !url.images.is_empty();
+            namespaces.video |= !url.videos.is_empty();
+            namespaces.news |= url.news.is_some();
+            namespaces.xhtml |= !url.alternates.is_empty();
+        }
+        namespaces
+    }
+}
 
 /// A writer for generating XML sitemaps.
 ///
-/// This struct provides methods to create sitemaps either by writing directly
-/// to a file or by building a String.
+/// A `SitemapWriter` streams entries to any [`std::io::Write`] sink one at a
+/// time, so arbitrarily large sitemaps can be produced without holding every
+/// [`SitemapUrl`] in memory. The convenience functions [`SitemapWriter::make`]
+/// and [`SitemapWriter::build`] cover the common cases of writing a whole list
+/// to a file or to a `String`.
 ///
 /// # Examples
 ///
+/// ## Streaming to any writer
+///
+/// ```rust
+/// use sitemap_writer::{SitemapWriter, SitemapUrl};
+///
+/// let mut writer = SitemapWriter::new(Vec::new()).unwrap();
+/// writer.push(&SitemapUrl::new("https://example.com/")).unwrap();
+/// writer.push(&SitemapUrl::new("https://example.com/about/")).unwrap();
+/// let bytes = writer.finish().unwrap();
+///
+/// let xml = String::from_utf8(bytes).unwrap();
+/// assert!(xml.contains("<loc>https://example.com/about/</loc>"));
+/// ```
+///
 /// ## Writing to a file
 ///
 /// ```rust,no_run
@@ -32,11 +126,278 @@ use crate::sitemap_url::SitemapUrl;
 /// ]);
 /// assert!(xml.contains("<loc>https://example.com/</loc>"));
 /// ```
-pub struct SitemapWriter {}
+pub struct SitemapWriter<W: Write = File> {
+    writer: W,
+    namespaces: SitemapNamespaces,
+    format: SitemapFormat,
+    news_cutoff: Option<SystemTime>,
+    dedup: Option<SitemapDedup>,
+    strict: bool,
+    entry_count: usize,
+    url_count: usize,
+    bytes_written: usize,
+}
+
+impl<W: Write> SitemapWriter<W> {
+    /// Starts a new sitemap on the given writer.
+    ///
+    /// The XML declaration and the opening `<urlset>` tag are written
+    /// immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Any [`std::io::Write`] sink, e.g. a `File`, a `TcpStream`,
+    ///   `Vec<u8>` or `std::io::stdout()`.
+    ///
+    /// # Returns
+    ///
+    /// Returns the writer on success, or a [`SitemapError`] if the header
+    /// cannot be written.
+    pub fn new(writer: W) -> Result<SitemapWriter<W>, SitemapError> {
+        SitemapWriter::with_namespaces(writer, SitemapNamespaces::default())
+    }
+
+    /// Starts a new sitemap on the given writer, declaring the given
+    /// extension namespaces on the `<urlset>` element.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Any [`std::io::Write`] sink.
+    /// * `namespaces` - The extension namespaces used by the entries that
+    ///   will be pushed.
+    pub fn with_namespaces(
+        writer: W,
+        namespaces: SitemapNamespaces,
+    ) -> Result<SitemapWriter<W>, SitemapError> {
+        SitemapWriter::with_format(writer, namespaces, SitemapFormat::default())
+    }
+
+    /// Starts a new sitemap on the given writer, declaring the given
+    /// extension namespaces and laying out the XML with the given format.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Any [`std::io::Write`] sink.
+    /// * `namespaces` - The extension namespaces used by the entries that
+    ///   will be pushed.
+    /// * `format` - The layout of the XML, see [`SitemapFormat`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapFormat, SitemapNamespaces, SitemapUrl, SitemapWriter};
+    ///
+    /// let mut writer = SitemapWriter::with_format(
+    ///     Vec::new(),
+    ///     SitemapNamespaces::default(),
+    ///     SitemapFormat::pretty(),
+    /// )
+    /// .unwrap();
+    /// writer.push(&SitemapUrl::new("https://example.com/")).unwrap();
+    /// let xml = String::from_utf8(writer.finish().unwrap()).unwrap();
+    /// assert!(xml.ends_with("  </url>\n</urlset>\n"));
+    /// ```
+    pub fn with_format(
+        writer: W,
+        namespaces: SitemapNamespaces,
+        format: SitemapFormat,
+    ) -> Result<SitemapWriter<W>, SitemapError> {
+        let mut sitemap = SitemapWriter {
+            writer,
+            namespaces,
+            format,
+            news_cutoff: None,
+            dedup: None,
+            strict: false,
+            entry_count: 0,
+            url_count: 0,
+            bytes_written: 0,
+        };
+        sitemap.write_text(&format.line(XML_HEADER, 0))?;
+        sitemap.write_text(&format.line(&urlset_open(&namespaces), 0))?;
+        Ok(sitemap)
+    }
+
+    /// Writes a single `<url>` entry.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The [`SitemapUrl`] to append to the sitemap.
+    ///
+    /// # Returns
+    ///
+    /// Returns an error without writing anything if:
+    ///
+    /// * the entry uses an extension that was not declared
+    ///   ([`SitemapError::UndeclaredNamespace`]),
+    /// * it has more than 1,000 images, this is a news sitemap that already
+    ///   holds 1,000 URLs, or the sitemap would exceed 50,000 URLs or 50 MB
+    ///   ([`SitemapError::LimitExceeded`]),
+    /// * one of its videos violates the video extension constraints
+    ///   ([`SitemapError::InvalidVideo`]),
+    /// * the writer is in strict mode and the entry is invalid
+    ///   ([`SitemapError::InvalidEntry`]).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapWriter, SitemapUrl};
+    ///
+    /// let mut writer = SitemapWriter::new(std::io::sink()).unwrap();
+    /// writer.push(&SitemapUrl::new("https://example.com/")).unwrap();
+    /// assert_eq!(writer.url_count(), 1);
+    /// ```
+    pub fn push(&mut self, url: &SitemapUrl) -> Result<(), SitemapError> {
+        let index = self.entry_count;
+        self.entry_count += 1;
+        let normalized = match &self.dedup {
+            Some(dedup) => match dedup.prepare(url) {
+                Some(normalized) => Some(normalized),
+                None => return Ok(()),
+            },
+            None => None,
+        };
+        let url = normalized.as_ref().unwrap_or(url);
+        let row = prepare_url(
+            url,
+            &self.namespaces,
+            self.news_cutoff,
+            self.strict,
+            index,
+            self.url_count,
+        )?;
+        if let Some(row) = row {
+            let row = self.format.element(&row, 1);
+            let close = self.format.line(URLSET_CLOSE, 0);
+            check_limits(self.url_count, self.bytes_written + row.len() + close.len())?;
+            self.push_laid_out_row(&row)?;
+            if let Some(dedup) = &mut self.dedup {
+                dedup.mark_written(&url.loc);
+            }
+        }
+        Ok(())
+    }
+
+    /// Enables or disables strict mode.
+    ///
+    /// In strict mode, [`SitemapWriter::push`] refuses entries for which
+    /// [`SitemapUrl::validate`] reports problems, returning
+    /// [`SitemapError::InvalidEntry`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapError, SitemapUrl, SitemapWriter};
+    ///
+    /// let mut writer = SitemapWriter::new(Vec::new()).unwrap().strict(true);
+    /// let res = writer.push(&SitemapUrl::new("/relative/"));
+    /// assert!(matches!(res, Err(SitemapError::InvalidEntry(_))));
+    /// ```
+    pub fn strict(mut self, strict: bool) -> SitemapWriter<W> {
+        self.strict = strict;
+        self
+    }
+
+    /// Silently skips news articles published more than two days before
+    /// `now`, keeping a news sitemap compliant.
+    ///
+    /// URLs without a `news` entry are not affected. The streaming writers
+    /// ([`SitemapWriter`], [`crate::ShardedSitemapWriter`] and the async
+    /// writers) can filter; [`SitemapWriter::make`] and
+    /// [`SitemapWriter::build`] write every URL they are given.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::SystemTime;
+    /// use sitemap_writer::{SitemapNamespaces, SitemapNews, SitemapUrl, SitemapWriter};
+    ///
+    /// let namespaces = SitemapNamespaces {
+    ///     news: true,
+    ///     ..SitemapNamespaces::default()
+    /// };
+    /// let mut writer = SitemapWriter::with_namespaces(Vec::new(), namespaces)
+    ///     .unwrap()
+    ///     .skip_stale_news(SystemTime::now());
+    ///
+    /// let mut url = SitemapUrl::new("https://example.com/news/old");
+    /// url.news = Some(SitemapNews::new("The Example Times", "en", "2008-12-23", "Old news"));
+    /// writer.push(&url).unwrap();
+    /// assert_eq!(writer.url_count(), 0);
+    /// ```
+    pub fn skip_stale_news(mut self, now: SystemTime) -> SitemapWriter<W> {
+        self.news_cutoff = Some(now);
+        self
+    }
+
+    /// Normalizes the `loc` of each URL before writing it, and silently
+    /// skips URLs whose normalized `loc` was already written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapNormalizer, SitemapUrl, SitemapWriter};
+    ///
+    /// let mut writer = SitemapWriter::new(Vec::new())
+    ///     .unwrap()
+    ///     .normalize(SitemapNormalizer::new());
+    /// writer.push(&SitemapUrl::new("https://Example.com/a")).unwrap();
+    /// writer.push(&SitemapUrl::new("https://example.com/a?utm_source=x")).unwrap();
+    /// assert_eq!(writer.url_count(), 1);
+    ///
+    /// let xml = String::from_utf8(writer.finish().unwrap()).unwrap();
+    /// assert!(xml.contains("<loc>https://example.com/a</loc>"));
+    /// ```
+    pub fn normalize(mut self, normalizer: SitemapNormalizer) -> SitemapWriter<W> {
+        self.dedup = Some(SitemapDedup::new(normalizer));
+        self
+    }
+
+    /// Writes a `<url>` element that was already laid out with the writer's
+    /// format.
+    pub(crate) fn push_laid_out_row(&mut self, row: &str) -> Result<(), SitemapError> {
+        self.write_text(row)?;
+        self.url_count += 1;
+        Ok(())
+    }
+
+    /// Writes the closing `</urlset>` tag, flushes and returns the underlying
+    /// writer.
+    pub fn finish(mut self) -> Result<W, SitemapError> {
+        let close = self.format.line(URLSET_CLOSE, 0);
+        self.write_text(&close)?;
+        match self.writer.flush() {
+            Ok(_) => Ok(self.writer),
+            Err(e) => Err(SitemapError::Write(e.to_string())),
+        }
+    }
+
+    /// Returns the number of `<url>` entries written so far.
+    pub fn url_count(&self) -> usize {
+        self.url_count
+    }
+
+    /// Returns the number of bytes written so far, including the header.
+    pub fn bytes_written(&self) -> usize {
+        self.bytes_written
+    }
+
+    fn write_text(&mut self, str: &str) -> Result<(), SitemapError> {
+        if let Err(e) = self.writer.write_all(str.as_bytes()) {
+            return Err(SitemapError::Write(e.to_string()));
+        }
+        self.bytes_written += str.len();
+        Ok(())
+    }
+}
 
 impl SitemapWriter {
     /// Creates a sitemap XML file at the specified path.
     ///
+    /// The file is written to a temporary file in the same directory and
+    /// renamed over `path` once complete, so an existing sitemap is only
+    /// replaced if the whole write succeeded.
+    ///
     /// # Arguments
     ///
     /// * `path` - The file path where the sitemap will be written.
@@ -58,47 +419,93 @@ impl SitemapWriter {
     ///         lastmod: Some("2024-01-01".to_string()),
     ///         changefreq: Some(SitemapChangeFreq::DAILY),
     ///         priority: Some(1.0),
+    ///         ..SitemapUrl::default()
     ///     },
     /// ]);
     /// assert!(result.is_ok());
     /// ```
     pub fn make(path: &str, urls: Vec<SitemapUrl>) -> Result<(), SitemapError> {
-        let file = File::create(path);
-        if file.is_err() {
-            return Err(SitemapError::FileOpen(file.err().unwrap().to_string()));
-        }
-        let mut file = file.unwrap();
-        write_text(&file, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
-        write_text(
-            &file,
-            r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#,
-        )?;
+        SitemapWriter::make_with_format(path, urls, SitemapFormat::default())
+    }
 
-        for url in urls {
-            let mut row = "<url>".to_string();
-            row += format!("<loc>{}</loc>", html_escape::encode_text(url.loc.as_str())).as_str();
-            if url.lastmod.is_some() {
-                row += format!("<lastmod>{}</lastmod>", url.lastmod.unwrap_or_default()).as_str();
-            }
-            if url.changefreq.is_some() {
-                row += format!(
-                    "<changefreq>{}</changefreq>",
-                    url.changefreq.unwrap().to_string()
-                )
-                .as_str();
-            }
-            if url.priority.is_some() {
-                row += format!("<priority>{}</priority>", url.priority.unwrap()).as_str();
-            }
+    /// Creates a sitemap XML file at the specified path, laid out with the
+    /// given format.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::{SitemapFormat, SitemapUrl, SitemapWriter};
+    ///
+    /// let result = SitemapWriter::make_with_format(
+    ///     "sitemap.xml",
+    ///     vec![SitemapUrl::new("https://example.com/")],
+    ///     SitemapFormat::pretty(),
+    /// );
+    /// assert!(result.is_ok());
+    /// ```
+    pub fn make_with_format(
+        path: &str,
+        urls: Vec<SitemapUrl>,
+        format: SitemapFormat,
+    ) -> Result<(), SitemapError> {
+        write_file(SitemapFile::create(path)?, &urls, format)
+    }
 
-            row += "</url>";
-            write_text(&file, row.as_str())?;
-        }
-        write_text(&file, r#"</urlset> "#)?;
-        match file.flush() {
-            Ok(_) => Ok(()),
-            Err(e) => Err(SitemapError::Write(e.to_string())),
-        }
+    /// Creates a gzip-compressed sitemap file (e.g. `sitemap.xml.gz`) at the
+    /// specified path.
+    ///
+    /// Requires the `gzip` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use sitemap_writer::{SitemapWriter, SitemapUrl};
+    ///
+    /// let result = SitemapWriter::make_gz("sitemap.xml.gz", vec![
+    ///     SitemapUrl::new("https://example.com/"),
+    /// ]);
+    /// assert!(result.is_ok());
+    /// ```
+    #[cfg(feature = "gzip")]
+    pub fn make_gz(path: &str, urls: Vec<SitemapUrl>) -> Result<(), SitemapError> {
+        SitemapWriter::make_gz_with_format(path, urls, SitemapFormat::default())
+    }
+
+    /// Creates a gzip-compressed sitemap file at the specified path, laid
+    /// out with the given format.
+    ///
+    /// Requires the `gzip` feature.
+    #[cfg(feature = "gzip")]
+    pub fn make_gz_with_format(
+        path: &str,
+        urls: Vec<SitemapUrl>,
+        format: SitemapFormat,
+    ) -> Result<(), SitemapError> {
+        write_file(SitemapFile::create_gz(path)?, &urls, format)
+    }
+
+    /// Validates every URL and returns a diagnostic for each invalid one.
+    ///
+    /// Neither [`SitemapWriter::make`] nor [`SitemapWriter::build`] validate
+    /// their input; call this first or use a writer in strict mode.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapUrl, SitemapWriter};
+    ///
+    /// let mut invalid = SitemapUrl::new("https://example.com/b");
+    /// invalid.lastmod = Some("yesterday".to_string());
+    ///
+    /// let diagnostics = SitemapWriter::validate(&[
+    ///     SitemapUrl::new("https://example.com/a"),
+    ///     invalid,
+    /// ]);
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(diagnostics[0].index, 1);
+    /// ```
+    pub fn validate(urls: &[SitemapUrl]) -> Vec<SitemapDiagnostic> {
+        diagnose(urls, |url| &url.loc, SitemapUrl::validate)
     }
 
     /// Builds a sitemap XML string from the provided URLs.
@@ -107,6 +514,9 @@ impl SitemapWriter {
     /// writing to a file, for example when serving the sitemap dynamically
     /// from a web server.
     ///
+    /// Unlike [`SitemapWriter::make`] and [`SitemapWriter::push`], this method
+    /// does not check extension limits; entries are written as given.
+    ///
     /// # Arguments
     ///
     /// * `urls` - A vector of [`SitemapUrl`] to include in the sitemap.
@@ -126,6 +536,7 @@ impl SitemapWriter {
     ///         lastmod: Some("2024-01-01".to_string()),
     ///         changefreq: Some(SitemapChangeFreq::WEEKLY),
     ///         priority: Some(0.8),
+    ///         ..SitemapUrl::default()
     ///     },
     ///     SitemapUrl::new("https://example.com/blog/"),
     /// ]);
@@ -134,38 +545,219 @@ impl SitemapWriter {
     /// // HttpResponse::Ok().content_type("application/xml").body(xml)
     /// ```
     pub fn build(urls: Vec<SitemapUrl>) -> String {
-        let mut content = "".to_string();
-        content.push_str(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
-        content.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
-        for url in urls {
-            let mut row = "<url>".to_string();
-            row += format!("<loc>{}</loc>", html_escape::encode_text(url.loc.as_str())).as_str();
-            if url.lastmod.is_some() {
-                row += format!("<lastmod>{}</lastmod>", url.lastmod.unwrap_or_default()).as_str();
-            }
-            if url.changefreq.is_some() {
-                row += format!(
-                    "<changefreq>{}</changefreq>",
-                    url.changefreq.unwrap().to_string()
-                )
-                .as_str();
-            }
-            if url.priority.is_some() {
-                row += format!("<priority>{}</priority>", url.priority.unwrap()).as_str();
-            }
-            row += "</url>";
-            content.push_str(&row);
-        }
+        SitemapWriter::build_with_format(urls, SitemapFormat::default())
+    }
 
-        content.push_str(r#"</urlset> "#);
+    /// Builds a sitemap XML string from the provided URLs, laid out with the
+    /// given format.
+    ///
+    /// See [`SitemapWriter::build`] and [`SitemapFormat`].
+    pub fn build_with_format(urls: Vec<SitemapUrl>, format: SitemapFormat) -> String {
+        let mut content = String::new();
+        content.push_str(&format.line(XML_HEADER, 0));
+        content.push_str(&format.line(&urlset_open(&SitemapNamespaces::from_urls(&urls)), 0));
+        for url in &urls {
+            content.push_str(&format.element(&render_url(url), 1));
+        }
+        content.push_str(&format.line(URLSET_CLOSE, 0));
         content
     }
 }
 
-fn write_text(mut file: &File, str: &str) -> Result<(), SitemapError> {
-    let f = file.write(str.as_bytes());
-    if f.is_err() {
-        return Err(SitemapError::Write(f.err().unwrap().to_string()));
+#[cfg(feature = "gzip")]
+impl<W: Write> SitemapWriter<GzEncoder<W>> {
+    /// Starts a new gzip-compressed sitemap on the given writer.
+    ///
+    /// [`SitemapWriter::bytes_written`] still reports the uncompressed size,
+    /// which is what the 50 MB protocol limit applies to. Use
+    /// [`SitemapWriter::finish_gz`] to close the sitemap and write the gzip
+    /// trailer.
+    ///
+    /// Requires the `gzip` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use sitemap_writer::{SitemapWriter, SitemapUrl};
+    ///
+    /// let mut writer = SitemapWriter::new_gz(Vec::new()).unwrap();
+    /// writer.push(&SitemapUrl::new("https://example.com/")).unwrap();
+    /// let compressed = writer.finish_gz().unwrap();
+    /// assert_eq!(&compressed[..2], &[0x1f, 0x8b]);
+    /// ```
+    pub fn new_gz(writer: W) -> Result<SitemapWriter<GzEncoder<W>>, SitemapError> {
+        SitemapWriter::new(GzEncoder::new(writer, Compression::default()))
+    }
+
+    /// Writes the closing `</urlset>` tag and the gzip trailer, and returns
+    /// the underlying writer.
+    pub fn finish_gz(self) -> Result<W, SitemapError> {
+        match self.finish()?.finish() {
+            Ok(writer) => Ok(writer),
+            Err(e) => Err(SitemapError::Write(e.to_string())),
+        }
+    }
+}
+
+fn write_file(
+    file: SitemapFile,
+    urls: &[SitemapUrl],
+    format: SitemapFormat,
+) -> Result<(), SitemapError> {
+    let mut writer = SitemapWriter::with_format(file, SitemapNamespaces::from_urls(urls), format)?;
+    for url in urls {
+        writer.push(url)?;
+    }
+    writer.finish()?.close()
+}
+
+/// Applies the checks of [`SitemapWriter::push`] to a URL and renders it.
+///
+/// `index` is the position of the URL among the entries pushed so far,
+/// including skipped ones, and `url_count` the number of entries written.
+/// Returns `None` if the URL is a stale news article that should be skipped.
+pub(crate) fn prepare_url(
+    url: &SitemapUrl,
+    namespaces: &SitemapNamespaces,
+    news_cutoff: Option<SystemTime>,
+    strict: bool,
+    index: usize,
+    url_count: usize,
+) -> Result<Option<String>, SitemapError> {
+    if let (Some(now), Some(news)) = (news_cutoff, &url.news)
+        && !news.is_recent(now)
+    {
+        return Ok(None);
+    }
+    if strict {
+        check_valid(url, index)?;
+    }
+    check_url(url, namespaces)?;
+    if namespaces.news && url_count >= MAX_URLS_PER_NEWS_SITEMAP {
+        return Err(SitemapError::LimitExceeded(format!(
+            "a news sitemap may contain at most {} URLs",
+            MAX_URLS_PER_NEWS_SITEMAP
+        )));
+    }
+    Ok(Some(render_url(url)))
+}
+
+/// Refuses a `<url>` entry that would take a sitemap past the protocol
+/// limits.
+///
+/// `url_count` is the number of entries already written and `size` the size
+/// of the sitemap once the entry and the closing tag are written.
+pub(crate) fn check_limits(url_count: usize, size: usize) -> Result<(), SitemapError> {
+    if url_count >= MAX_URLS_PER_SITEMAP {
+        return Err(SitemapError::LimitExceeded(format!(
+            "a sitemap may contain at most {} URLs",
+            MAX_URLS_PER_SITEMAP
+        )));
+    }
+    if size > MAX_SITEMAP_BYTES {
+        return Err(SitemapError::LimitExceeded(format!(
+            "a sitemap may be at most {} bytes",
+            MAX_SITEMAP_BYTES
+        )));
+    }
+    Ok(())
+}
+
+/// Renders the opening `<urlset>` tag with the given extension namespaces.
+pub(crate) fn urlset_open(namespaces: &SitemapNamespaces) -> String {
+    let mut tag = format!(r#"<urlset xmlns="{}""#, SITEMAP_NS);
+    if namespaces.image {
+        tag += format!(r#" xmlns:image="{}""#, IMAGE_NS).as_str();
+    }
+    if namespaces.video {
+        tag += format!(r#" xmlns:video="{}""#, VIDEO_NS).as_str();
+    }
+    if namespaces.news {
+        tag += format!(r#" xmlns:news="{}""#, NEWS_NS).as_str();
+    }
+    if namespaces.xhtml {
+        tag += format!(r#" xmlns:xhtml="{}""#, XHTML_NS).as_str();
+    }
+    tag += ">";
+    tag
+}
+
+/// Checks that a `<url>` entry only uses declared namespaces and stays within
+/// the extension limits.
+pub(crate) fn check_url(
+    url: &SitemapUrl,
+    namespaces: &SitemapNamespaces,
+) -> Result<(), SitemapError> {
+    if !url.images.is_empty() && !namespaces.image {
+        return Err(SitemapError::UndeclaredNamespace("image".to_string()));
+    }
+    if url.images.len() > MAX_IMAGES_PER_URL {
+        return Err(SitemapError::LimitExceeded(format!(
+            "{} has {} images, at most {} are allowed",
+            url.loc,
+            url.images.len(),
+            MAX_IMAGES_PER_URL
+        )));
+    }
+    if !url.videos.is_empty() && !namespaces.video {
+        return Err(SitemapError::UndeclaredNamespace("video".to_string()));
+    }
+    if url.news.is_some() && !namespaces.news {
+        return Err(SitemapError::UndeclaredNamespace("news".to_string()));
+    }
+    if !url.alternates.is_empty() && !namespaces.xhtml {
+        return Err(SitemapError::UndeclaredNamespace("xhtml".to_string()));
+    }
+    for video in &url.videos {
+        video.validate()?;
+        if video.content_loc.as_deref() == Some(url.loc.as_str()) {
+            return Err(SitemapError::InvalidVideo(format!(
+                "content_loc must differ from the page URL {}",
+                url.loc
+            )));
+        }
     }
     Ok(())
 }
+
+/// Refuses an entry with validation issues.
+pub(crate) fn check_valid(url: &SitemapUrl, index: usize) -> Result<(), SitemapError> {
+    let issues = url.validate();
+    if issues.is_empty() {
+        return Ok(());
+    }
+    Err(SitemapError::InvalidEntry(SitemapDiagnostic {
+        index,
+        loc: url.loc.clone(),
+        issues,
+    }))
+}
+
+/// Renders a single `<url>` element.
+pub(crate) fn render_url(url: &SitemapUrl) -> String {
+    let mut row = "<url>".to_string();
+    row += format!("<loc>{}</loc>", html_escape::encode_text(url.loc.as_str())).as_str();
+    if let Some(lastmod) = &url.lastmod {
+        row += format!("<lastmod>{}</lastmod>", html_escape::encode_text(lastmod)).as_str();
+    }
+    if let Some(changefreq) = &url.changefreq {
+        row += format!("<changefreq>{}</changefreq>", changefreq).as_str();
+    }
+    if let Some(priority) = url.priority {
+        row += format!("<priority>{}</priority>", priority).as_str();
+    }
+    for alternate in &url.alternates {
+        row += render_alternate(alternate).as_str();
+    }
+    for image in &url.images {
+        row += render_image(image).as_str();
+    }
+    for video in &url.videos {
+        row += render_video(video).as_str();
+    }
+    if let Some(news) = &url.news {
+        row += render_news(news).as_str();
+    }
+    row += "</url>";
+    row
+}
@@ -0,0 +1,127 @@
+use std::fmt::{Display, Formatter};
+
+use crate::sitemap_datetime::parse_timestamp;
+
+/// The maximum length of a `<loc>` URL, in characters.
+pub const MAX_LOC_LEN: usize = 2_048;
+
+/// A single problem found in a sitemap entry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SitemapIssue {
+    /// `loc` is empty.
+    EmptyLoc,
+    /// `loc` is not an absolute `http` or `https` URL.
+    InvalidLoc(String),
+    /// `loc` is longer than 2,048 characters.
+    LocTooLong(usize),
+    /// `priority` is outside `0.0..=1.0`.
+    PriorityOutOfRange(f32),
+    /// `lastmod` is not in W3C Datetime format.
+    InvalidLastmod(String),
+}
+
+impl Display for SitemapIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SitemapIssue::EmptyLoc => write!(f, "loc is empty"),
+            SitemapIssue::InvalidLoc(loc) => {
+                write!(f, "loc is not an absolute http(s) URL: {}", loc)
+            }
+            SitemapIssue::LocTooLong(len) => write!(
+                f,
+                "loc is {} characters long, at most {} are allowed",
+                len, MAX_LOC_LEN
+            ),
+            SitemapIssue::PriorityOutOfRange(priority) => {
+                write!(f, "priority {} is outside 0.0..=1.0", priority)
+            }
+            SitemapIssue::InvalidLastmod(lastmod) => {
+                write!(f, "lastmod is not a W3C Datetime: {}", lastmod)
+            }
+        }
+    }
+}
+
+/// The problems found in one entry of a list of sitemap entries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapDiagnostic {
+    /// The position of the entry in the validated list, or among the entries
+    /// pushed to a strict writer, starting at 0.
+    pub index: usize,
+    /// The `loc` of the entry, as given.
+    pub loc: String,
+    /// Every problem found in the entry.
+    pub issues: Vec<SitemapIssue>,
+}
+
+impl Display for SitemapDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let issues: Vec<String> = self.issues.iter().map(|i| i.to_string()).collect();
+        write!(
+            f,
+            "entry {} ({}): {}",
+            self.index,
+            self.loc,
+            issues.join(", ")
+        )
+    }
+}
+
+/// Returns a diagnostic for every invalid entry.
+pub(crate) fn diagnose<T>(
+    entries: &[T],
+    loc: impl Fn(&T) -> &str,
+    validate: impl Fn(&T) -> Vec<SitemapIssue>,
+) -> Vec<SitemapDiagnostic> {
+    let mut diagnostics = vec![];
+    for (index, entry) in entries.iter().enumerate() {
+        let issues = validate(entry);
+        if !issues.is_empty() {
+            diagnostics.push(SitemapDiagnostic {
+                index,
+                loc: loc(entry).to_string(),
+                issues,
+            });
+        }
+    }
+    diagnostics
+}
+
+/// Checks that `loc` is a non-empty, absolute `http`/`https` URL of at most
+/// 2,048 characters.
+pub(crate) fn validate_loc(loc: &str) -> Vec<SitemapIssue> {
+    let mut issues = vec![];
+    if loc.is_empty() {
+        issues.push(SitemapIssue::EmptyLoc);
+        return issues;
+    }
+    if !is_absolute_http_url(loc) {
+        issues.push(SitemapIssue::InvalidLoc(loc.to_string()));
+    }
+    let len = loc.chars().count();
+    if len > MAX_LOC_LEN {
+        issues.push(SitemapIssue::LocTooLong(len));
+    }
+    issues
+}
+
+/// Checks that `lastmod` is a W3C Datetime.
+pub(crate) fn validate_lastmod(lastmod: &str) -> Option<SitemapIssue> {
+    match parse_timestamp(lastmod) {
+        Some(_) => None,
+        None => Some(SitemapIssue::InvalidLastmod(lastmod.to_string())),
+    }
+}
+
+fn is_absolute_http_url(loc: &str) -> bool {
+    let rest = match loc.split_once("://") {
+        Some((scheme, rest))
+            if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") =>
+        {
+            rest
+        }
+        _ => return false,
+    };
+    let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    !host.is_empty() && !loc.chars().any(char::is_whitespace)
+}